@@ -0,0 +1,95 @@
+use std::io::{self, BufRead, Write};
+use chrono::NaiveTime;
+use core::data_managing::Storage;
+use core::doctor::{self, CloseAt};
+use core::time_spec;
+use crate::errors;
+use crate::timer::{EXIT_ERROR, EXIT_OK};
+
+// "schedule", "midnight" or a HH:MM time
+pub fn parse_close_at(s: &str, schedule_end: NaiveTime) -> Result<CloseAt, String> {
+    match s {
+        "schedule" => Ok(CloseAt::ScheduleEnd(schedule_end)),
+        "midnight" => Ok(CloseAt::Midnight),
+        _ => time_spec::parse_at(s).map(CloseAt::Time).map_err(|e| e.to_string()),
+    }
+}
+
+// Lists entries left running on previous days and closes them when `fix` is set
+pub fn run(storage: &dyn Storage, fix: bool, close_at: CloseAt) -> i32 {
+    let today = storage.clock().today();
+    let report = match doctor::check(storage, today) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to check the entries: {}", errors::describe(&e));
+            return EXIT_ERROR;
+        }
+    };
+    for (date, reason) in &report.malformed {
+        println!("Unreadable day {}: {}", date, reason);
+    }
+    if report.dangling.is_empty() {
+        println!("No entries left running on previous days");
+    }
+
+    let mut code = if report.malformed.is_empty() { EXIT_OK } else { EXIT_ERROR };
+    for dangling in &report.dangling {
+        if !fix {
+            println!("Left running: {}", dangling);
+            continue;
+        }
+        match doctor::close_dangling(storage, dangling, close_at, today) {
+            Ok(()) => println!("Closed: {}", dangling),
+            Err(e) => {
+                println!("Could not close {}: {:#}", dangling, e);
+                code = EXIT_ERROR;
+            }
+        }
+    }
+    if !fix && !report.dangling.is_empty() {
+        println!("Run doctor --fix to close them");
+    }
+    code
+}
+
+// Before the interactive prompt, asks what to do with each forgotten entry
+pub fn ask_on_startup(storage: &dyn Storage, schedule_end: NaiveTime) {
+    let today = storage.clock().today();
+    let Ok(report) = doctor::check(storage, today) else {
+        return;
+    };
+    for dangling in &report.dangling {
+        println!("Left running: {}", dangling);
+        print!("Close it at HH:MM, (s)chedule end {}, (m)idnight or Enter to leave it: ", schedule_end.format("%H:%M"));
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer).is_err() {
+            return;
+        }
+        let close_at = match answer.trim() {
+            "" => continue,
+            "s" => CloseAt::ScheduleEnd(schedule_end),
+            "m" => CloseAt::Midnight,
+            time => match time_spec::parse_at(time) {
+                Ok(time) => CloseAt::Time(time),
+                Err(e) => {
+                    println!("{:#}, left as it was", e);
+                    continue;
+                }
+            },
+        };
+        match doctor::close_dangling(storage, dangling, close_at, today) {
+            Ok(()) => println!("Closed"),
+            Err(e) => println!("Could not close it: {:#}", e),
+        }
+    }
+}
+
+// The non-interactive commands only warn, they must not wait for input
+pub fn warn_on_startup(storage: &dyn Storage) {
+    if let Ok(report) = doctor::check(storage, storage.clock().today())
+        && !report.dangling.is_empty() {
+        eprintln!("{} entries from previous days are still running, see doctor --fix", report.dangling.len());
+    }
+}
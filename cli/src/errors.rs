@@ -0,0 +1,17 @@
+use core::data_managing::error::StorageError;
+
+// How an error of core is shown to the user, with a hint when there is something to do
+pub fn describe(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<StorageError>() {
+        Some(StorageError::PermissionDenied { path }) => {
+            format!("Permission denied on {}, check who owns the data folder", path.display())
+        },
+        Some(StorageError::MalformedLine { path, error }) => {
+            format!("{} line {}: {} ({:?}), fix it by hand and try again", path.display(), error.line, error.reason, error.content)
+        },
+        // The message already contains the source
+        Some(e) => e.to_string(),
+        None => format!("{:#}", error),
+    }
+}
+
@@ -0,0 +1,76 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use core::config::Keybindings;
+
+// A key of the config file, like "esc", "tab" or "ctrl-q"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn parse(name: &str) -> Result<Key, String> {
+        let name = name.trim().to_lowercase();
+        let (modifiers, key) = match name.rsplit_once('-').filter(|(_, key)| !key.is_empty()) {
+            Some(("ctrl", key)) => (KeyModifiers::CONTROL, key),
+            Some(("alt", key)) => (KeyModifiers::ALT, key),
+            Some((modifier, _)) => return Err(format!("Unknown modifier '{}' in key '{}'", modifier, name)),
+            None => (KeyModifiers::NONE, name.as_str()),
+        };
+        let code = match key {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("Unknown key '{}'", name)),
+                }
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+
+    // Shift is ignored, it only changes which character or tab arrives
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.modifiers == event.modifiers.difference(KeyModifiers::SHIFT)
+    }
+}
+
+// The keybindings of the config file, ready to compare with key events
+#[derive(Debug, Clone, Copy)]
+pub struct Keys {
+    pub quit: Key,
+    pub confirm: Key,
+    pub next: Key,
+    pub previous: Key,
+    pub page_next: Key,
+    pub page_previous: Key,
+}
+
+impl Keys {
+    pub fn from_config(keybindings: &Keybindings) -> Result<Keys, String> {
+        Ok(Keys {
+            quit: Key::parse(&keybindings.quit)?,
+            confirm: Key::parse(&keybindings.confirm)?,
+            next: Key::parse(&keybindings.next)?,
+            previous: Key::parse(&keybindings.previous)?,
+            page_next: Key::parse(&keybindings.page_next)?,
+            page_previous: Key::parse(&keybindings.page_previous)?,
+        })
+    }
+
+    // Bound keys are not typed into the prompt
+    pub fn is_bound(&self, event: &KeyEvent) -> bool {
+        [self.quit, self.confirm, self.next, self.previous, self.page_next, self.page_previous].iter().any(|key| key.matches(event))
+    }
+}
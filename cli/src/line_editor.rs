@@ -0,0 +1,154 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// The text of a prompt and where the cursor is in it. The cursor moves by grapheme,
+// so "ñ" written as "n" and a tilde or an emoji are one step
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    // Byte offset, always at the start of a grapheme
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Replaces the text and leaves the cursor at the end
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.cursor = 0;
+        self.insert(text);
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.cursor == self.text.len()
+    }
+
+    // Columns taken by the text before the cursor, wide characters take two
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.cursor].width()
+    }
+
+    // Typed or pasted text. A prompt is a single line, line breaks and tabs become spaces
+    pub fn insert(&mut self, text: &str) {
+        let text: String = text.trim_end_matches(['\r', '\n'])
+            .chars()
+            .filter_map(|c| match c {
+                '\r' | '\n' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    // Editing and moving keys. False when the key is not one of them, like Enter
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            // AltGr arrives as Control and Alt together
+            KeyCode::Char(c) if control == alt => self.insert(c.encode_utf8(&mut [0; 4])),
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::Char('e') if control => self.cursor = self.text.len(),
+            KeyCode::Char('w') if control => {
+                let start = self.word_start();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            },
+            KeyCode::Char('u') if control => {
+                self.text.replace_range(..self.cursor, "");
+                self.cursor = 0;
+            },
+            KeyCode::Backspace => {
+                let start = self.previous_grapheme();
+                self.text.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            },
+            KeyCode::Delete => {
+                let end = self.next_grapheme();
+                self.text.replace_range(self.cursor..end, "");
+            },
+            KeyCode::Left => self.cursor = self.previous_grapheme(),
+            KeyCode::Right => self.cursor = self.next_grapheme(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn previous_grapheme(&self) -> usize {
+        self.text[..self.cursor].grapheme_indices(true).next_back().map_or(0, |(start, _)| start)
+    }
+
+    fn next_grapheme(&self) -> usize {
+        self.cursor + self.text[self.cursor..].graphemes(true).next().map_or(0, str::len)
+    }
+
+    // Like Ctrl-W in a shell, the spaces before the cursor and the word before them
+    fn word_start(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let word_end = before.trim_end().len();
+        before[..word_end].rfind(char::is_whitespace).map_or(0, |space| space + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut LineEditor, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        editor.handle_key(&KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn the_cursor_moves_by_grapheme_and_counts_columns() {
+        let mut editor = LineEditor::default();
+        // "ñ" as "n" and a combining tilde, and an emoji two columns wide
+        editor.set_text("Disen\u{303}o 🎨");
+        assert_eq!(editor.cursor_width(), 9);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(editor.cursor_width(), 5);
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!((editor.text(), editor.cursor_width()), ("Diseo 🎨", 4));
+        press(&mut editor, KeyCode::Char('ñ'), KeyModifiers::NONE);
+        press(&mut editor, KeyCode::End, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Char('X'), KeyModifiers::SHIFT);
+        assert_eq!((editor.text(), editor.cursor_width()), ("Diseño 🎨X", 10));
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!((editor.text(), editor.cursor_width()), ("iseño 🎨X", 0));
+        assert!(!press(&mut editor, KeyCode::Enter, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn word_and_line_deletes_and_paste() {
+        let mut editor = LineEditor::default();
+        editor.set_text("fix the  login bug  ");
+        press(&mut editor, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "fix the  login ");
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Char('w'), KeyModifiers::CONTROL);
+        assert_eq!(editor.text(), "fix the   ");
+        press(&mut editor, KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!((editor.text(), editor.cursor_width()), (" ", 0));
+
+        editor.insert("two\nlines\tand a bell\u{7}\n");
+        assert_eq!(editor.text(), "two lines and a bell ");
+        // Control letters are not typed
+        assert!(press(&mut editor, KeyCode::Char('e'), KeyModifiers::CONTROL));
+        assert_eq!(editor.text(), "two lines and a bell ");
+    }
+}
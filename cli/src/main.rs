@@ -1,478 +1,623 @@
-use std::fs;
-use std::fs::{File, OpenOptions};
-use std::io::Read;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::time::Duration;
 use chrono::prelude::*;
-use crossterm::style::{Print};
-use regex::Regex;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event},
     terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
     cursor,
     execute,
+    queue,
 };
-use core::{utils, data_managing::text_storage};
-use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use core::{data_managing::{Storage, day_log::Entry, error::StorageError, layout::Layout, lmdb_storage, text_storage::{self, TextStorage}}};
+use core::clock::{self, Clock, FixedClock, SystemClock};
+use core::history;
+use core::config::{self, Config};
+use clap::{Parser, Subcommand, ValueEnum};
+use keys::Keys;
+use selector::{Action, Selector};
+
+mod doctor;
+mod errors;
+mod keys;
+mod line_editor;
+mod report;
+mod selector;
+mod status;
+mod timer;
+mod tui;
 
 // automatiza --help y --version
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Name of the person to greet
-    #[arg(short, long, default_value = "./data")]
-    config_path: std::path::PathBuf,
+    /// Folder where projects and weeks are stored, overrides data_dir of the config file
+    #[arg(short, long, global = true)]
+    config_path: Option<PathBuf>,
+
+    /// Where projects, tasks and entries are kept, overrides storage of the config file
+    #[arg(long, value_enum, global = true)]
+    storage: Option<Backend>,
+
+    /// Without a command it starts or ends today's entry
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Backend {
+    /// Projects and Weeks folders with plain text files
+    Text,
+    /// LMDB database inside the data folder (see the migrate command)
+    Lmdb,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Rewrite old "project_task" lines in the week folders with quoted names
+    MigrateNames,
+    /// Move day files to the folder of their ISO week-year
+    MigrateWeeks {
+        /// Only list the files that would be moved
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Move every day file to a new layout, like "{year}/{month}/{date:%Y-%m-%d}.txt"
+    Reorganize {
+        /// Path template inside the data folder, "{year}-{month}.txt" keeps a file per month.
+        /// The layout key of config.toml when missing
+        layout: Option<String>,
+    },
+    /// Import every project and week file into the LMDB database
+    Migrate,
+    /// Time per project and task for a day or a week
+    Report {
+        #[command(subcommand)]
+        period: ReportPeriod,
+
+        #[arg(long, value_enum, default_value = "table", global = true)]
+        format: report::Format,
+    },
+    /// Start a timer without prompts, creating the project and task if needed
+    Start {
+        #[arg(short, long)]
+        project: String,
+        #[arg(short, long)]
+        task: String,
+        #[command(flatten)]
+        when: When,
+    },
+    /// Stop the running timer
+    Stop {
+        #[arg(short, long, default_value = "")]
+        description: String,
+        #[command(flatten)]
+        when: When,
+    },
+    /// Stop the running timer and start another one
+    Switch {
+        #[arg(short, long)]
+        project: String,
+        #[arg(short, long)]
+        task: String,
+        /// Description for the entry being stopped
+        #[arg(short, long, default_value = "")]
+        description: String,
+        #[command(flatten)]
+        when: When,
+    },
+    /// Drop the running timer without saving it
+    Cancel {
+        /// Restore the last cancelled entry
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Find entries left running on previous days
+    Doctor {
+        /// Close them without asking
+        #[arg(long)]
+        fix: bool,
+        /// "schedule" (end of the work day, or the start if it began later), "midnight" (split at 00:00) or a HH:MM time
+        #[arg(long, default_value = "schedule")]
+        close_at: String,
+    },
+    /// Overtime or undertime per week and month against the schedule of the config file
+    Balance {
+        /// First day, the first day with entries by default
+        #[arg(long)]
+        from: Option<String>,
+        /// Last day, today by default
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Settings of the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Start the last finished project and task again
+    Again {
+        /// Choose among the last N different activities instead
+        #[arg(long, value_name = "N")]
+        pick: Option<usize>,
+        #[command(flatten)]
+        when: When,
+    },
+    /// Show what is running and for how long
+    Status {
+        /// Keep the line updated every second until q or Esc
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Full screen dashboard with the running timer, today's entries and the week totals
+    Tui,
+}
+
+// For when the action happened a while ago ("empecé hace 5 mins")
+#[derive(clap::Args, Debug)]
+struct When {
+    /// How long ago, like 5m or 1h30m
+    #[arg(long, conflicts_with = "at")]
+    ago: Option<String>,
+    /// Time of today as HH:MM
+    #[arg(long)]
+    at: Option<String>,
+}
+
+impl When {
+    fn resolve(&self, clock: &dyn Clock) -> Option<NaiveTime> {
+        match timer::resolve_time(clock, self.ago.as_deref(), self.at.as_deref()) {
+            Ok(time) => time,
+            Err(code) => std::process::exit(code),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the values in use, after the defaults and the command line flags
+    Show,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReportPeriod {
+    /// DATE as YYYY-MM-DD or dd-mm-YYYY, today by default
+    Day { date: Option<String> },
+    /// ISO-WEEK as YYYY-Www (2026-W01), this week by default
+    Week { week: Option<String> },
+}
+
+// The date format of the config, YYYY-MM-DD or dd-mm-YYYY
+fn parse_date(date: &str, config: &Config) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, &config.date_format)
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .or_else(|_| NaiveDate::parse_from_str(date, "%d-%m-%Y"))
+        .map_err(|_| format!("'{}' is not a YYYY-MM-DD date", date))
+}
+
+fn run_balance(storage: &dyn Storage, config: &Config, from: Option<&str>, to: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let to = match to {
+        Some(to) => parse_date(to, config)?,
+        None => storage.clock().today(),
+    };
+    let from = match from {
+        Some(from) => parse_date(from, config)?,
+        None => storage.days()?.first().copied().unwrap_or(to),
+    };
+    if from > to {
+        return Err(format!("{} is after {}", from, to).into());
+    }
+    let balance = core::balance::balance(storage, &config.schedule, config.week_start, from, to)?;
+    print!("{}", report::render_balance(&balance, config));
+    Ok(())
+}
+
+fn run_report(storage: &dyn Storage, config: &Config, period: &ReportPeriod, format: report::Format) -> Result<(), Box<dyn std::error::Error>> {
+    let today = storage.clock().today();
+    let result = match period {
+        ReportPeriod::Day { date } => {
+            let date = match date {
+                Some(date) => parse_date(date, config)?,
+                None => today,
+            };
+            core::report::day_report(storage, date)?
+        },
+        ReportPeriod::Week { week } => {
+            let (year, week) = match week {
+                Some(week) => core::report::parse_iso_week(week)
+                    .ok_or_else(|| format!("'{}' is not a YYYY-Www week", week))?,
+                None => core::report::current_week(today, config.week_start),
+            };
+            core::report::week_report(storage, year, week, config.week_start)?
+        },
+    };
+    print!("{}", report::render(&result, format, config)?);
+    Ok(())
+}
+
+fn migrate(data_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = lmdb_storage::LmdbStorage::open(data_path)?;
+    let report = lmdb_storage::import_text_storage(data_path, &storage)?;
+    println!("{:>10} {:>10} {:>10}", "", "files", "database");
+    println!("{:>10} {:>10} {:>10}", "projects", report.expected.projects, report.stored.projects);
+    println!("{:>10} {:>10} {:>10}", "tasks", report.expected.tasks, report.stored.tasks);
+    println!("{:>10} {:>10} {:>10}", "entries", report.expected.entries, report.stored.entries);
+    if !report.is_complete() {
+        return Err("The database does not match the text files".into());
+    }
+    println!("Migration completed");
+    Ok(())
+}
+
+fn migrate_names(data_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let report = text_storage::migrate_legacy_names(data_path)?;
+    println!("{} entries migrated in {} files", report.migrated_entries, report.rewritten_files);
+    if !report.unmapped.is_empty() {
+        println!("{} lines could not be mapped and were left as they were:", report.unmapped.len());
+        for (file, line, reason) in &report.unmapped {
+            println!("  {}: {} ({})", file.display(), line, reason);
+        }
+    }
+    Ok(())
 }
 
-fn start_record_note(args: Args) {
+fn migrate_weeks(data_path: &Path, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let report = text_storage::migrate_weeks(data_path, dry_run)?;
+    let verb = if dry_run { "Would move" } else { "Moved" };
+    for (from, to) in &report.moves {
+        println!("{} {} -> {}", verb, from.display(), to.display());
+    }
+    for (from, to) in &report.conflicts {
+        println!("Skipped {}, {} already exists", from.display(), to.display());
+    }
+    if dry_run {
+        println!("{} files would be moved", report.moves.len());
+    } else {
+        println!("{} files moved", report.moves.len());
+    }
+    Ok(())
+}
+
+// The layout of config.toml is the one to move to, a different one can't be given
+fn reorganize(data_path: &Path, layout: Option<&str>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let layout = match (layout, config.layout.as_deref()) {
+        (Some(layout), Some(configured)) if Layout::parse(layout)? != Layout::parse(configured)? => {
+            return Err(format!("config.toml sets the layout '{}', change it there and run reorganize without a layout", configured).into());
+        },
+        (Some(layout), _) | (None, Some(layout)) => Layout::parse(layout)?,
+        (None, None) => return Err("Give the new layout or set the layout key in config.toml".into()),
+    };
+    let report = text_storage::reorganize(data_path, &layout)?;
+    println!("{} days moved from {} files into {} files", report.days, report.files_before, report.files_after);
+    println!("Layout is now {}", layout.template());
+    Ok(())
+}
+
+fn start_record_note(storage: &dyn Storage, keys: &Keys) {
     // Confirms that needed files exists
-    text_storage::init(&args.config_path);
+    if let Err(e) = storage.init() {
+        eprintln!("Failed to prepare the storage: {}", errors::describe(&e));
+    }
 
     // Get list of proyects
-    let projects: Vec<String> = text_storage::get_projects(&args.config_path);
+    let projects: Vec<String> = storage.get_projects().unwrap_or_else(|e| {
+        eprintln!("Failed to read projects: {}", errors::describe(&e));
+        Vec::new()
+    });
+
+    // The most used projects and tasks go first. A day that can't be read only loses the order
+    let frecency = history::frecency(storage).unwrap_or_default();
+    let boosts = projects.iter().map(|project| frecency.project(project)).collect();
 
     // Needed variables
     let mut selected_project: String = "".to_string();
-    let mut project_tasks: Vec<String> = Vec::new();
-    let mut selector: Vec<String> = Vec::new();
-    let re = Regex::new(r"\\([0-9])$").unwrap();
-    let mut input_buffer = String::new();
-    let mut tab_selector : Option<usize> = None;
+    let mut selector = Selector::new(Vec::new(), *keys);
+    selector.set_boosted_candidates(projects, boosts);
+    let mut stdout = io::stdout();
 
-    // Activar modo raw
+    // Activar modo raw, lo pegado llega entero y no tecla a tecla
     enable_raw_mode().unwrap();
-    print!("> ");
-    io::stdout().flush().unwrap();
-    execute!(io::stdout(), cursor::SavePosition).unwrap();
-    print!("\r\n{:?}", projects);
-    // Volver al final de la línea de entrada
-    execute!(
-        io::stdout(),
-        cursor::RestorePosition
-    ).unwrap();
-    io::stdout().flush().unwrap();
+    let _ = execute!(stdout, EnableBracketedPaste);
+    let _ = selector.render(&mut stdout, status::terminal_columns());
 
     loop {
         // Leer evento del teclado
-        if let Ok(Event::Key(KeyEvent { code, .. })) = event::read() {
-            match code {
-                KeyCode::Char(c) => {
-                    tab_selector = None;
-                    //TODO: Meter un prompt de qué proyecto está el usuario
-                    // Agregar carácter al buffer
-                    input_buffer.push(c);
-
-                    // Redibujar todo
-                    let _ = execute!(
-                        io::stdout(),
-                        //cursor::MoveTo(0, cursor::position().unwrap().1),
-                        cursor::MoveToColumn(0),
-                        Clear(ClearType::FromCursorDown),
-                        cursor::SavePosition
-                    );
-
-                    // Mostrar la línea de entrada
-                    print!("> {}\r\n", input_buffer);
-
-                    // Mostrar el buffer debajo
-                    if selected_project.is_empty() {
-                        selector = utils::order_vector(&input_buffer, &projects);
-                    } else {
-                        selector = utils::order_vector(&input_buffer, &project_tasks);
+        let key = match event::read() {
+            Ok(Event::Key(key)) => key,
+            Ok(Event::Paste(text)) => {
+                selector.paste(&text);
+                let _ = selector.render(&mut stdout, status::terminal_columns());
+                continue;
+            },
+            _ => continue,
+        };
+        //TODO: Añadir signals para que hagan cosas (crtl+c, etc)
+        match selector.handle_key(&key) {
+            Action::Edit => {},
+            Action::Quit => {
+                let _ = selector.finish(&mut stdout);
+                println!("Saliendo del programa...\r");
+                break;
+            },
+            Action::Submit { input, picked } => {
+                let _ = selector.finish(&mut stdout);
+                if selected_project.is_empty() {
+                    let project = match picked {
+                        Some(project) => Some(project),
+                        None if input.is_empty() => None,
+                        None => match storage.create_project(&input) {
+                            Ok(()) => {
+                                print!("Archivo creado exitosamente.\r\n");
+                                Some(input)
+                            },
+                            // Typing the name of a project that exists picks it
+                            Err(e) if matches!(e.downcast_ref(), Some(StorageError::AlreadyExists(_))) => Some(input),
+                            Err(e) => {
+                                eprintln!("Failed to create project: {}\r", errors::describe(&e));
+                                None
+                            },
+                        },
+                    };
+                    if let Some(project) = project {
+                        let tasks = storage.get_tasks(&project).unwrap_or_else(|e| {
+                            eprintln!("Failed to read the tasks: {}\r", errors::describe(&e));
+                            Vec::new()
+                        });
+                        let boosts = tasks.iter().map(|task| frecency.task(&project, task)).collect();
+                        selector.set_boosted_candidates(tasks, boosts);
+                        selected_project = project;
                     }
-                    print!("{:?}", selector);
-
-                    // Volver al final de la línea de entrada
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                    ).unwrap();
-                    io::stdout().flush().unwrap();
-
-                    // Verificar si el buffer termina con "/num"
-                    if let Some(caps) = re.captures(&input_buffer) {
-                        let number = &caps[1].parse::<usize>().unwrap();  // This is "5"
-                        //TODO: Quitar los .txt del search
-
-                        // Read project file and populate project_tasks
-                        if selected_project.is_empty() {
-                            //let project_path = format!("{}/{}.txt", text_storage::PROYECTOS_PATH, &file_names[*number]);
-                            selected_project = selector[*number].to_string();
-                            match text_storage::get_tasks_from_project(
-                                &args.config_path,
-                                &selector[*number]
-                            ) {
-                                Ok(returned_tasks) => project_tasks = returned_tasks,
-                                Err(e) => eprintln!("Failed to create project: {}", e)
-                            }
-                            input_buffer.clear();
-                        } else {
-                            match text_storage::start_timer_on_task(&args.config_path, &selected_project, &selector[*number]) {
+                } else {
+                    match timer::prompt_time(storage.clock(), &input) {
+                        Ok((typed_task, start)) => {
+                            // A task picked from the list or a new one typed by the user
+                            let task = match picked {
+                                Some(task) => task,
+                                None => {
+                                    if let Err(e) = storage.create_task(&selected_project, &typed_task) {
+                                        eprintln!("Failed to create task: {}\r", errors::describe(&e));
+                                    }
+                                    typed_task
+                                }
+                            };
+                            match storage.start_timer_at(&selected_project, &task, start) {
                                 Ok(()) => break,
-                                Err(e) => eprintln!("Failed to create project: {}", e)
+                                Err(e) => eprintln!("Failed to start timer: {}\r", errors::describe(&e))
                             }
-                            break;
-                        }
+                        },
+                        Err(e) => eprintln!("{}\r", e),
                     }
                 }
-                KeyCode::Enter => {
-
-                    // Limpiar todo desde el cursor hacia abajo
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo(0, cursor::position().unwrap().1),
-                        Clear(ClearType::FromCursorDown)
-                    ).unwrap();
-
-                    // Procesar la línea completa
-                    print!("\r\n");
-                    let user_input = input_buffer.trim().to_string();
-
-                    if selected_project.is_empty() {
-                        if tab_selector.is_none() {
-                            // Ensure text_storage::PROYECTOS_PATH exists
-                            match text_storage::create_project(&args.config_path,&user_input) {
-                                Ok(returned_project) => selected_project = returned_project,
-                                Err(e) => eprintln!("Failed to create project: {}", e)
-                            }
-                        } else {
-                            selected_project = selector[tab_selector.unwrap()].to_string();
-                            match text_storage::get_tasks_from_project(
-                                &args.config_path,
-                                &selected_project
-                            ) {
-                                Ok(returned_tasks) => project_tasks = returned_tasks,
-                                Err(e) => eprintln!("Failed to create project: {}", e)
-                            }
-                            print!("{:?}", project_tasks);
-                            
-                        }
-                        //TODO: testear este caso
-                    } else {
-                        if tab_selector.is_none() {
-                            text_storage::create_task(&args.config_path, &selected_project, &user_input);
-                            match text_storage::start_timer_on_task(&args.config_path, &selected_project, &input_buffer.trim().to_string()) {
-                                Ok(()) => break,
-                                Err(e) => eprintln!("Failed to start timer on new task: {}", e)
-                            }
-                        } else {
-                            match text_storage::start_timer_on_task(
-                                &args.config_path,
-                                &selected_project,
-                                &selector[tab_selector.unwrap()].to_string())
-                                {
-                                Ok(()) => break,
-                                Err(e) => eprintln!("Failed to start timer on existing task: {}", e)
-                            }
-                        }
-                    }
+            },
+        }
+        let _ = selector.render(&mut stdout, status::terminal_columns());
+    }
 
-                    print!("\r\n");
-                    input_buffer.clear();
-                    io::stdout().flush().unwrap();
-                }
-                KeyCode::Backspace => {
-                    tab_selector = None;
-                    // Borrar último carácter
-                    if !input_buffer.is_empty() {
-                        input_buffer.pop();
-
-                        // Redibujar todo
-                        execute!(
-                            io::stdout(),
-                            cursor::MoveTo(0, cursor::position().unwrap().1),
-                            Clear(ClearType::FromCursorDown)
-                        ).unwrap();
-
-                        // Mostrar la línea de entrada
-                        print!("> {}\r\n", input_buffer);
-
-                        // Mostrar el buffer debajo
-                        // TODO: esto se puede refactorizar porque se hace mucho
-                        if selected_project.is_empty() {
-                            selector = utils::order_vector(&input_buffer, &projects);
-                        } else {
-                            selector = utils::order_vector(&input_buffer, &project_tasks);
-                        }
-                        print!("{:?}", selector);
-
-                        // Volver al final de la línea de entrada
-                        execute!(
-                            io::stdout(),
-                            cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                        ).unwrap();
-                        io::stdout().flush().unwrap();
-                    }
-                }
-                KeyCode::Tab => {
-                    // Redibujar todo
-                    let _ = execute!(
-                        io::stdout(),
-                        cursor::MoveTo(0, cursor::position().unwrap().1),
-                        Clear(ClearType::FromCursorDown)
-                    );
-
-                    // Mostrar la línea de entrada
-                    print!(">{}\r\n", input_buffer);
-
-                    // Mostrar el buffer debajo
-                    if selected_project.is_empty() {
-                        selector = utils::order_vector(&input_buffer, &projects);
-                    } else {
-                        selector = utils::order_vector(&input_buffer, &project_tasks);
-                    }
-                    if tab_selector == None {
-                        tab_selector = Some(0);
-                    } else {
-                        if tab_selector.unwrap() >= selector.len() -1 {
-                            tab_selector = Some(0);
-                        } else {
-                            tab_selector = Some(tab_selector.unwrap() + 1);
-                        }
-                    }
+    // Desactivar modo raw al salir
+    let _ = execute!(stdout, DisableBracketedPaste);
+    disable_raw_mode().unwrap();
 
-                    // Assuming selector is something like a Vec<String> or Vec<&str>
-                    for (i, item) in selector.iter().enumerate() {
-                        if Some(i) == tab_selector {
-                            // Highlighted item
-                            let _ = execute!(
-                                io::stdout(),
-                                crossterm::style::SetAttribute(crossterm::style::Attribute::Reverse),
-                                Print(item),
-                                crossterm::style::SetAttribute(crossterm::style::Attribute::NoReverse),
-                            );
-                        } else {
-                            // Normal item
-                            print!("{}", item);
-                        }
-                        if i != selector.len() - 1 {
-                            print!(", ");
-                        }
-                    }
+}
 
-                    // Volver al final de la línea de entrada
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                    ).unwrap();
-                    io::stdout().flush().unwrap();
-                }
-                KeyCode::BackTab => {
-                    // Redibujar todo
-                    let _ = execute!(
-                        io::stdout(),
-                        cursor::MoveTo(0, cursor::position().unwrap().1),
-                        Clear(ClearType::FromCursorDown)
-                    );
-
-                    // Mostrar la línea de entrada
-                    print!(">{}\r\n", input_buffer);
-
-                    // Mostrar el buffer debajo
-                    if selected_project.is_empty() {
-                        selector = utils::order_vector(&input_buffer, &projects);
-                    } else {
-                        selector = utils::order_vector(&input_buffer, &project_tasks);
-                    }
-                    if tab_selector == None || tab_selector.unwrap() == 0 {
-                        tab_selector = Some(selector.len() -1);
-                    } else {
-                        tab_selector = Some(tab_selector.unwrap() - 1);
-                    }
+// La línea de estado va encima del prompt y se redibuja en su sitio
+fn draw_stop_screen(out: &mut impl Write, status_line: &str, selector: &Selector, redraw: bool) -> io::Result<()> {
+    let columns = status::terminal_columns();
+    if redraw {
+        queue!(out, cursor::MoveUp(1))?;
+    }
+    queue!(out, cursor::MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+    write!(out, "{}\r\n", status::fit(status_line, columns))?;
+    selector.render(out, columns)
+}
 
-                    // Assuming selector is something like a Vec<String> or Vec<&str>
-                    for (i, item) in selector.iter().enumerate() {
-                        if Some(i) == tab_selector {
-                            // Highlighted item
-                            let _ = execute!(
-                                io::stdout(),
-                                crossterm::style::SetAttribute(crossterm::style::Attribute::Reverse),
-                                Print(item),
-                                crossterm::style::SetAttribute(crossterm::style::Attribute::NoReverse),
-                            );
-                        } else {
-                            // Normal item
-                            print!("{}", item);
-                        }
-                        if i != selector.len() - 1 {
-                            print!(", ");
-                        }
-                    }
+fn end_record_note(storage: &dyn Storage, config: &Config, keys: &Keys, running: &(NaiveDate, Entry)) {
+    // Sin candidatos el selector es solo la línea de entrada
+    let mut selector = Selector::new(Vec::new(), *keys);
+    let mut stdout = io::stdout();
+    let mut drawn = false;
 
-                    // Volver al final de la línea de entrada
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                    ).unwrap();
-                    io::stdout().flush().unwrap();
-                }
-                //TODO: Añadir signals para que hagan cosas (crtl+c, etc)
-                KeyCode::Esc => {
-                    let _ = execute!( io::stdout(), 
-                        cursor::RestorePosition,
-                        Clear(ClearType::FromCursorDown),
-                    );
-                    let _ = io::stdout().flush();
-                    println!("> Saliendo del programa...\r");
-                    break;
+    // Activar modo raw, lo pegado llega entero y no tecla a tecla
+    enable_raw_mode().unwrap();
+    let _ = execute!(stdout, EnableBracketedPaste);
+
+    loop {
+        let status_line = status::running_line(storage.clock(), config, Some(running));
+        let _ = draw_stop_screen(&mut stdout, &status_line, &selector, drawn);
+        drawn = true;
+
+        // Sin teclas se redibuja cada segundo para que avance el contador,
+        // un cambio de tamaño también vuelve a dibujar
+        if !event::poll(Duration::from_secs(1)).unwrap_or(false) {
+            continue;
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) => key,
+            Ok(Event::Paste(text)) => {
+                selector.paste(&text);
+                continue;
+            },
+            _ => continue,
+        };
+        match selector.handle_key(&key) {
+            // Verificar si el buffer termina con "\q"
+            Action::Edit if selector.input().ends_with("\\q") => {
+                let _ = selector.finish(&mut stdout);
+                println!("Saliendo del programa...\r");
+                break;
+            },
+            Action::Edit => {},
+            Action::Quit => {
+                let _ = selector.finish(&mut stdout);
+                println!("Saliendo del programa...\r");
+                break;
+            },
+            Action::Submit { input, .. } => {
+                let _ = selector.finish(&mut stdout);
+                let stopped = timer::prompt_time(storage.clock(), &input).and_then(|(description, end)| {
+                    storage.stop_timer_at(&description, end).map_err(|e| errors::describe(&e))
+                });
+                if let Err(e) = stopped {
+                    eprintln!("Failed to end the entry: {}\r", e);
                 }
-                _ => {}
-            }
-            let _ = execute!( io::stdout(), cursor::RestorePosition);
-            let _ = io::stdout().flush();
+                break;
+            },
         }
     }
 
     // Desactivar modo raw al salir
+    let _ = execute!(stdout, DisableBracketedPaste);
     disable_raw_mode().unwrap();
 
 }
 
-fn end_record_note(args: Args) {
-    let filename_path_buf = text_storage::get_todays_filename(&args.config_path);
-    let filename_path = filename_path_buf.as_path();
-    // Activar modo raw
-    enable_raw_mode().unwrap();
-
-    let mut input_buffer = String::new();
+// The data folder says where its files are. An empty one takes the layout of
+// config.toml, else a different layout needs reorganize to move the files first
+fn check_layout(data_path: &Path, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(configured) = &config.layout else {
+        return Ok(());
+    };
+    let configured = Layout::parse(configured)?;
+    let current = Layout::load(data_path)?;
+    if configured == current {
+        return Ok(());
+    }
+    if text_storage::get_day_files(data_path, &current)?.is_empty() {
+        configured.save(data_path)?;
+        return Ok(());
+    }
+    Err(format!("The day files use the layout '{}' but config.toml sets '{}', run reorganize to move them",
+        current.template(), configured.template()).into())
+}
 
-    print!("> ");
-    io::stdout().flush().unwrap();
-    loop {
-        // Leer evento del teclado
-        if let Ok(Event::Key(KeyEvent { code, .. })) = event::read() {
-            match code {
-                KeyCode::Char(c) => {
-                    // Agregar carácter al buffer
-                    input_buffer.push(c);
-
-                    // Redibujar todo
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo(0, cursor::position().unwrap().1),
-                        Clear(ClearType::FromCursorDown)
-                    ).unwrap();
-
-                    // Mostrar la línea de entrada
-                    print!("> {}\r\n", input_buffer);
-
-                    // Volver al final de la línea de entrada
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                    ).unwrap();
-                    io::stdout().flush().unwrap();
-
-                    // Verificar si el buffer termina con "/q"
-                    if input_buffer.ends_with("\\q") {
-                        print!("\r\n\r\n");
-                        println!("Saliendo del programa...\r");
-                        break;
-                    }
-                }
-                KeyCode::Enter => {
-                    // Limpiar todo desde el cursor hacia abajo
-                    execute!(
-                        io::stdout(),
-                        cursor::MoveTo(0, cursor::position().unwrap().1),
-                        Clear(ClearType::FromCursorDown)
-                    ).unwrap();
-
-                    // Procesar la línea completa
-                    print!("\r\n");
-
-                        match OpenOptions::new()
-                            .append(true)
-                            .create(true)
-                            .open(&filename_path) {
-                            Ok(mut file) => {
-                                match writeln!(file,
-                                    "{}) {}",
-                                    input_buffer,
-                                    Local::now().format("%H:%M")
-                                ){
-                                    Ok(()) => {}
-                                    Err(e) => eprintln!("Failed to write in file: {}", e)
-                                }
-                            },
-                            Err(e) => eprintln!("Failed to create file: {}", e)
-                        }
+fn open_storage(backend: Backend, data_path: &Path, clock: Rc<dyn Clock>) -> Result<Box<dyn Storage>, Box<dyn std::error::Error>> {
+    Ok(match backend {
+        Backend::Text => Box::new(TextStorage::open(data_path)?.with_clock(clock)),
+        Backend::Lmdb => Box::new(lmdb_storage::LmdbStorage::open(data_path)?.with_clock(clock)),
+    })
+}
 
-                    input_buffer.clear();
-                    print!("> ");
-                    io::stdout().flush().unwrap();
-                    break;
-                }
-                KeyCode::Backspace => {
-                    // Borrar último carácter
-                    if !input_buffer.is_empty() {
-                        input_buffer.pop();
-
-                        // Redibujar todo
-                        execute!(
-                            io::stdout(),
-                            cursor::MoveTo(0, cursor::position().unwrap().1),
-                            Clear(ClearType::FromCursorDown)
-                        ).unwrap();
-
-                        // Mostrar la línea de entrada
-                        print!("> {}\r\n", input_buffer);
-
-                        // Volver al final de la línea de entrada
-                        execute!(
-                            io::stdout(),
-                            cursor::MoveTo((2 + input_buffer.len()) as u16, cursor::position().unwrap().1 - 1)
-                        ).unwrap();
-                        io::stdout().flush().unwrap();
-                    }
+// TIME_LOGGER_NOW stops the clock at the given time, else it is the time of the machine
+fn open_clock() -> Result<Rc<dyn Clock>, Box<dyn std::error::Error>> {
+    Ok(match std::env::var(clock::NOW_VARIABLE) {
+        Ok(now) => Rc::new(FixedClock::new(clock::parse_now(&now)?)),
+        Err(_) => Rc::new(SystemClock),
+    })
+}
 
-                }
-                KeyCode::Esc => {
-                    print!("\r\n\r\n");
-                    println!("Saliendo del programa...\r");
-                    break;
-                }
-                _ => {}
-            }
+fn show_config(config_file: &Path, config: &Config, data_path: &Path, backend: Backend) {
+    let exists = if config_file.exists() { "" } else { " (not found, using defaults)" };
+    println!("config_file = {}{}", config_file.display(), exists);
+    println!("data_dir = {}", data_path.display());
+    println!("storage = {}", backend.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default());
+    if let Ok(layout) = Layout::load(data_path) {
+        match &config.layout {
+            Some(configured) if Layout::parse(configured).ok().as_ref() != Some(&layout) => {
+                println!("layout = {} (the files are still in {}, run reorganize)", configured, layout.template());
+            },
+            _ => println!("layout = {}", layout.template()),
         }
     }
-
-    // Desactivar modo raw al salir
-    disable_raw_mode().unwrap();
-
+    println!("date_format = {}", config.date_format);
+    println!("time_format = {}", config.time_format);
+    println!("week_start = {}", config.week_start);
+    println!("schedule.start = {}", config.schedule.start.format("%H:%M"));
+    println!("schedule.end = {}", config.schedule.end.format("%H:%M"));
+    println!("schedule.break_minutes = {}", config.schedule.break_minutes);
+    println!("schedule.days = {}", config.schedule.days.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "));
+    println!("schedule.holidays = {}", config.schedule.holidays.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "));
+    println!("schedule.time_off = {}", config.schedule.time_off.iter()
+        .map(|off| format!("{}..{}", off.from, off.to.unwrap_or(off.from)))
+        .collect::<Vec<_>>().join(", "));
+    println!("keybindings.quit = {}", config.keybindings.quit);
+    println!("keybindings.confirm = {}", config.keybindings.confirm);
+    println!("keybindings.next = {}", config.keybindings.next);
+    println!("keybindings.previous = {}", config.keybindings.previous);
+    println!("keybindings.page_next = {}", config.keybindings.page_next);
+    println!("keybindings.page_previous = {}", config.keybindings.page_previous);
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Get all arguments
     let args = Args::parse();
 
-    // Construct todays filename
-    let todays_file_path: PathBuf = text_storage::get_todays_filename(&args.config_path);
-
-    // If todays file exists, is empty or complete start a new entry, else end the current note
-    if !todays_file_path.exists() || fs::metadata(&todays_file_path)?.len() == 0 {
-        start_record_note(args);
-    } else {
-        match File::open(&todays_file_path) {
-            Ok(mut file) => {
-                use std::io::Seek;
-                use std::io::SeekFrom;
+    // The flags win over the config file, the config file over the defaults
+    let config_file = config::config_file();
+    let config = Config::load(&config_file)?;
+    let data_path = args.config_path.clone().unwrap_or_else(|| config.data_dir());
+    let backend = match args.storage {
+        Some(backend) => backend,
+        None => Backend::from_str(&config.storage, true)
+            .map_err(|_| format!("Unknown storage '{}' in {}", config.storage, config_file.display()))?,
+    };
+    let keys = Keys::from_config(&config.keybindings)?;
+
+    match args.command {
+        Some(Command::MigrateNames) => return migrate_names(&data_path),
+        Some(Command::MigrateWeeks { dry_run }) => return migrate_weeks(&data_path, dry_run),
+        Some(Command::Reorganize { ref layout }) => return reorganize(&data_path, layout.as_deref(), &config),
+        Some(Command::Migrate) => return migrate(&data_path),
+        Some(Command::Config { action: ConfigAction::Show }) => {
+            show_config(&config_file, &config, &data_path, backend);
+            return Ok(());
+        },
+        _ => {}
+    }
 
-                // Seek to 2 bytes before the end
-                file.seek(SeekFrom::End(-1))?;
+    if matches!(backend, Backend::Text) {
+        check_layout(&data_path, &config)?;
+    }
+    let storage = open_storage(backend, &data_path, open_clock()?)?;
+
+    match &args.command {
+        Some(Command::Doctor { fix, close_at }) => {
+            let close_at = match doctor::parse_close_at(close_at, config.schedule.end) {
+                Ok(close_at) => close_at,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(timer::EXIT_INVALID_TIME)
+                }
+            };
+            std::process::exit(doctor::run(storage.as_ref(), *fix, close_at))
+        },
+        Some(_) => doctor::warn_on_startup(storage.as_ref()),
+        None => doctor::ask_on_startup(storage.as_ref(), config.schedule.end),
+    }
 
-                // Read the last 2 bytes
-                let mut buffer = vec![0u8; 1];
-                file.read_exact(&mut buffer)?;
+    // Exit codes are documented in timer.rs
+    match &args.command {
+        Some(Command::Report { period, format }) => return run_report(storage.as_ref(), &config, period, *format),
+        Some(Command::Balance { from, to }) => return run_balance(storage.as_ref(), &config, from.as_deref(), to.as_deref()),
+        Some(Command::Start { project, task, when }) => {
+            std::process::exit(timer::start(storage.as_ref(), &config, project, task, when.resolve(storage.clock())))
+        },
+        Some(Command::Stop { description, when }) => {
+            std::process::exit(timer::stop(storage.as_ref(), &config, description, when.resolve(storage.clock())))
+        },
+        Some(Command::Switch { project, task, description, when }) => {
+            std::process::exit(timer::switch(storage.as_ref(), &config, project, task, description, when.resolve(storage.clock())))
+        },
+        Some(Command::Cancel { undo }) => {
+            let backup = data_path.join(timer::CANCEL_BACKUP);
+            if *undo {
+                std::process::exit(timer::undo_cancel(storage.as_ref(), &config, &backup))
+            }
+            std::process::exit(timer::cancel(storage.as_ref(), &config, &backup))
+        },
+        Some(Command::Again { pick, when }) => {
+            std::process::exit(timer::again(storage.as_ref(), &config, *pick, when.resolve(storage.clock())))
+        },
+        Some(Command::Status { watch: false }) => std::process::exit(status::show(storage.as_ref(), &config)),
+        Some(Command::Status { watch: true }) => std::process::exit(status::watch(storage.as_ref(), &config, &keys)),
+        Some(Command::Tui) => std::process::exit(tui::run(storage.as_ref(), &config, keys)),
+        _ => {}
+    }
 
-                if buffer[0] == 10 {
-                    start_record_note(args);
-                } else {
-                    end_record_note(args);
-                }
-            },
-            Err(e) => eprintln!("Failed to open file: {}", e)
-        }
+    // If there is a running entry end it, else start a new one
+    match storage.running_session() {
+        Ok(Some(running)) => end_record_note(storage.as_ref(), &config, &keys, &running),
+        Ok(None) => start_record_note(storage.as_ref(), &keys),
+        Err(e) => eprintln!("Failed to read the running entry: {}", errors::describe(&e)),
     }
     std::process::exit(0);
 }
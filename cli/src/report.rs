@@ -0,0 +1,126 @@
+use core::balance::{Balance, PeriodBalance};
+use core::report::{format_minutes, Report};
+use core::config::Config;
+use clap::ValueEnum;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum Format {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+// Only the table follows the date and time formats of the config, JSON and CSV keep ISO dates
+pub fn render(report: &Report, format: Format, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match format {
+        Format::Table => render_table(report, config),
+        Format::Json => serde_json::to_string_pretty(report)?,
+        Format::Csv => render_csv(report),
+    })
+}
+
+fn render_table(report: &Report, config: &Config) -> String {
+    let mut out = String::new();
+    if report.from == report.to {
+        out.push_str(&format!("Report {}\n\n", report.from.format(&config.date_format)));
+    } else {
+        out.push_str(&format!("Report {} - {}\n\n", report.from.format(&config.date_format), report.to.format(&config.date_format)));
+    }
+
+    // Wide enough for the longest project or indented task
+    let width = report.projects.iter()
+        .flat_map(|p| std::iter::once(p.project.chars().count())
+            .chain(p.tasks.iter().map(|t| t.task.chars().count() + 2)))
+        .max()
+        .unwrap_or(0)
+        .max("Project / Task".len());
+
+    out.push_str(&format!("{:<width$}  {:>6}\n", "Project / Task", "Time", width = width));
+    for project in &report.projects {
+        out.push_str(&format!("{:<width$}  {:>6}\n", project.project, format_minutes(project.minutes), width = width));
+        for task in &project.tasks {
+            out.push_str(&format!("  {:<width$}  {:>6}\n", task.task, format_minutes(task.minutes), width = width - 2));
+        }
+    }
+    out.push_str(&format!("{:<width$}  {:>6}\n", "Total", format_minutes(report.minutes), width = width));
+
+    if report.running > 0 {
+        out.push_str(&format!("\n{} running entries not counted\n", report.running));
+    }
+
+    if !report.gaps.is_empty() {
+        out.push_str("\nGaps\n");
+        for gap in &report.gaps {
+            out.push_str(&format!("{} {}-{}  {:>6}\n",
+                gap.date.format(&config.date_format),
+                gap.from.format(&config.time_format),
+                gap.to.format(&config.time_format),
+                format_minutes(gap.minutes)
+            ));
+        }
+    }
+    out
+}
+
+// One row per task, project, total and gap so it can be filtered by the first column
+fn render_csv(report: &Report) -> String {
+    let mut out = String::from("kind,date,project,task,from,to,minutes\n");
+    let range = if report.from == report.to {
+        report.from.to_string()
+    } else {
+        format!("{}/{}", report.from, report.to)
+    };
+    for project in &report.projects {
+        for task in &project.tasks {
+            out.push_str(&format!("task,{},{},{},,,{}\n", range, csv_field(&project.project), csv_field(&task.task), task.minutes));
+        }
+        out.push_str(&format!("project,{},{},,,,{}\n", range, csv_field(&project.project), project.minutes));
+    }
+    out.push_str(&format!("total,{},,,,,{}\n", range, report.minutes));
+    for gap in &report.gaps {
+        out.push_str(&format!("gap,{},,,{},{},{}\n", gap.date, gap.from.format("%H:%M"), gap.to.format("%H:%M"), gap.minutes));
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn render_balance(balance: &Balance, config: &Config) -> String {
+    let mut out = format!("Balance {} - {}\n", balance.from.format(&config.date_format), balance.to.format(&config.date_format));
+    render_periods(&mut out, "Week", &balance.weeks);
+    render_periods(&mut out, "Month", &balance.months);
+    if balance.running > 0 {
+        out.push_str(&format!("\n{} running entries not counted\n", balance.running));
+    }
+    out.push_str(&format!("\nBalance {}\n", signed_minutes(balance.total())));
+    out
+}
+
+fn render_periods(out: &mut String, title: &str, periods: &[PeriodBalance]) {
+    out.push_str(&format!("\n{:<9}  {:>8}  {:>8}  {:>10}  {:>8}\n", title, "Expected", "Tracked", "Difference", "Balance"));
+    for period in periods {
+        out.push_str(&format!("{:<9}  {:>8}  {:>8}  {:>10}  {:>8}\n",
+            period.period,
+            format_minutes(period.expected),
+            format_minutes(period.tracked),
+            signed_minutes(period.difference),
+            signed_minutes(period.balance),
+        ));
+    }
+}
+
+// Overtime gets a '+' so it can't be mistaken for a total
+fn signed_minutes(minutes: i64) -> String {
+    if minutes > 0 {
+        format!("+{}", format_minutes(minutes))
+    } else {
+        format_minutes(minutes)
+    }
+}
@@ -0,0 +1,470 @@
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::ops::Range;
+use crossterm::event::KeyEvent;
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue};
+use unicode_width::UnicodeWidthStr;
+use core::{fuzzy, time_spec};
+use crate::keys::Keys;
+use crate::line_editor::LineEditor;
+use crate::status;
+
+// What the prompt has to do after a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    // The input or the highlighted candidate changed, keep reading keys
+    Edit,
+    // Enter, or "\N" at the end of the input. `picked` is the highlighted or labelled
+    // candidate, `input` the text typed before it
+    Submit { input: String, picked: Option<String> },
+    Quit,
+}
+
+// A prompt line with the candidates ranked by what has been typed shown below it.
+// Keys go in through handle_key and the screen is only touched by render
+pub struct Selector {
+    keys: Keys,
+    candidates: Vec<String>,
+    // Points added to the match of each candidate, see history::frecency
+    boosts: Vec<i64>,
+    input: LineEditor,
+    ranked: Vec<String>,
+    // Char indexes of each ranked candidate that matched the input
+    positions: Vec<Vec<usize>>,
+    highlighted: Option<usize>,
+    // A "\N" that doesn't pick anything, shown below the candidates
+    error: Option<String>,
+    // Width of the last render, the pages are cut with it
+    columns: Cell<u16>,
+}
+
+impl Selector {
+    pub fn new(candidates: Vec<String>, keys: Keys) -> Selector {
+        let mut selector = Selector {
+            keys,
+            candidates: Vec::new(),
+            boosts: Vec::new(),
+            input: LineEditor::default(),
+            ranked: Vec::new(),
+            positions: Vec::new(),
+            highlighted: None,
+            error: None,
+            columns: Cell::new(80),
+        };
+        selector.set_candidates(candidates);
+        selector
+    }
+
+    // Starts over with other candidates, like the tasks once the project is picked
+    pub fn set_candidates(&mut self, candidates: Vec<String>) {
+        self.set_boosted_candidates(candidates, Vec::new());
+    }
+
+    // The most used candidates go first, before anything is typed too
+    pub fn set_boosted_candidates(&mut self, candidates: Vec<String>, boosts: Vec<i64>) {
+        self.candidates = candidates;
+        self.boosts = boosts;
+        self.input.clear();
+        self.rank();
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.text()
+    }
+
+    // Columns from the start of the input to the cursor
+    pub fn cursor_width(&self) -> usize {
+        self.input.cursor_width()
+    }
+
+    // Starts the prompt with some text, like the description being edited
+    pub fn set_input(&mut self, input: &str) {
+        self.input.set_text(input);
+        self.rank();
+    }
+
+    // Pasted text goes in at the cursor like typed text, without picking a "\N"
+    pub fn paste(&mut self, text: &str) {
+        self.input.insert(text);
+        self.rank();
+    }
+
+    // The candidates in the order they are shown
+    pub fn ranked(&self) -> &[String] {
+        &self.ranked
+    }
+
+    // What matched in each ranked candidate
+    pub fn positions(&self) -> &[Vec<usize>] {
+        &self.positions
+    }
+
+    pub fn highlighted(&self) -> Option<&str> {
+        self.highlighted.map(|index| self.ranked[index].as_str())
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent) -> Action {
+        let before = self.input.text().to_string();
+        match key.code {
+            _ if !self.keys.is_bound(key) && self.input.handle_key(key) => {
+                if self.input.text() == before {
+                    return Action::Edit;
+                }
+                self.rank();
+                // Only typing at the end completes a "\N"
+                if self.input.text().len() > before.len() && self.input.at_end() {
+                    self.quick_select().unwrap_or(Action::Edit)
+                } else {
+                    Action::Edit
+                }
+            },
+            // A "\1" waiting for a second digit is picked as it is
+            _ if self.keys.confirm.matches(key) => match self.label() {
+                Some((typed, number)) => match self.labelled(number) {
+                    Some(picked) => Action::Submit { input: typed.trim().to_string(), picked: Some(picked.clone()) },
+                    None => {
+                        self.error = Some(self.missing_label(number));
+                        Action::Edit
+                    },
+                },
+                None => Action::Submit {
+                    input: self.input.text().trim().to_string(),
+                    picked: self.highlighted().map(str::to_string),
+                },
+            },
+            _ if self.keys.next.matches(key) => {
+                self.highlighted = match self.highlighted {
+                    _ if self.ranked.is_empty() => None,
+                    Some(index) if index + 1 < self.ranked.len() => Some(index + 1),
+                    _ => Some(0),
+                };
+                Action::Edit
+            },
+            _ if self.keys.previous.matches(key) => {
+                self.highlighted = match self.highlighted {
+                    _ if self.ranked.is_empty() => None,
+                    Some(index) if index > 0 => Some(index - 1),
+                    _ => Some(self.ranked.len() - 1),
+                };
+                Action::Edit
+            },
+            _ if self.keys.page_next.matches(key) => {
+                let pages = self.pages(self.columns.get());
+                let page = (self.current_page(&pages) + 1).min(pages.len().saturating_sub(1));
+                self.highlighted = pages.get(page).map(|page| page.start);
+                Action::Edit
+            },
+            _ if self.keys.page_previous.matches(key) => {
+                let pages = self.pages(self.columns.get());
+                let page = self.current_page(&pages).saturating_sub(1);
+                self.highlighted = pages.get(page).map(|page| page.start);
+                Action::Edit
+            },
+            _ if self.keys.quit.matches(key) => Action::Quit,
+            _ => Action::Edit,
+        }
+    }
+
+    // The candidates split in lines of `columns`, a page always has one at least
+    pub fn pages(&self, columns: u16) -> Vec<Range<usize>> {
+        let columns = usize::from(columns.max(1)) - 1;
+        let widths: Vec<usize> = self.ranked.iter().enumerate().map(|(index, item)| labelled_width(index, item)).collect();
+        let total = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+        // Room kept at the end of a page for " (10/10)", there are never more pages than candidates
+        let mark = 4 + 2 * self.ranked.len().to_string().len();
+        let room = if total <= columns { columns } else { columns.saturating_sub(mark) };
+        let mut pages = Vec::new();
+        let mut start = 0;
+        let mut width = 0;
+        for (index, item_width) in widths.into_iter().enumerate() {
+            if index > start && width + 2 + item_width > room {
+                pages.push(start..index);
+                start = index;
+                width = item_width;
+            } else if index > start {
+                width += 2 + item_width;
+            } else {
+                width = item_width;
+            }
+        }
+        if start < self.ranked.len() {
+            pages.push(start..self.ranked.len());
+        }
+        pages
+    }
+
+    // The page of the highlighted candidate, the first one without it
+    fn current_page(&self, pages: &[Range<usize>]) -> usize {
+        self.highlighted
+            .and_then(|highlighted| pages.iter().position(|page| page.contains(&highlighted)))
+            .unwrap_or(0)
+    }
+
+    // Draws the prompt on the current line and the page of candidates on the next
+    // one, then leaves the cursor at the end of the input. Only relative moves are
+    // used, the terminal is never asked where the cursor is
+    pub fn render(&self, out: &mut impl Write, columns: u16) -> io::Result<()> {
+        self.columns.set(columns);
+        queue!(out, cursor::MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+        write!(out, "> {}", self.input.text())?;
+        let mut rows = 0;
+        if !self.ranked.is_empty() {
+            write!(out, "\r\n")?;
+            let pages = self.pages(columns);
+            let current = self.current_page(&pages);
+            let mut width = 0;
+            for index in pages[current].clone() {
+                let item = &self.ranked[index];
+                if index > pages[current].start {
+                    write!(out, ", ")?;
+                    width += 2;
+                }
+                if Some(index) == self.highlighted {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                write!(out, "{}:", index)?;
+                // Matched characters are underlined
+                for (i, c) in item.chars().enumerate() {
+                    if self.positions[index].contains(&i) {
+                        queue!(out, SetAttribute(Attribute::Underlined), Print(c), SetAttribute(Attribute::NoUnderline))?;
+                    } else {
+                        write!(out, "{}", c)?;
+                    }
+                }
+                if Some(index) == self.highlighted {
+                    queue!(out, SetAttribute(Attribute::NoReverse))?;
+                }
+                width += labelled_width(index, item);
+            }
+            if pages.len() > 1 {
+                let mark = format!(" ({}/{})", current + 1, pages.len());
+                width += mark.len();
+                write!(out, "{}", mark)?;
+            }
+            // A candidate longer than the line wraps, the prompt is that many rows above
+            rows += 1 + width.saturating_sub(1) / usize::from(columns.max(1));
+        }
+        if let Some(error) = &self.error {
+            write!(out, "\r\n{}", status::fit(error, columns))?;
+            rows += 1;
+        }
+        if rows > 0 {
+            queue!(out, cursor::MoveUp(rows as u16))?;
+        }
+        queue!(out, cursor::MoveToColumn((2 + self.input.cursor_width()) as u16))?;
+        out.flush()
+    }
+
+    // Leaves the typed line on screen without the candidates and starts an empty
+    // prompt on the next line
+    pub fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, cursor::MoveToColumn(0), Clear(ClearType::FromCursorDown))?;
+        write!(out, "> {}\r\n", self.input.text())?;
+        self.input.clear();
+        self.rank();
+        out.flush()
+    }
+
+    // Every match is kept, the ones that don't fit are reached by pages
+    fn rank(&mut self) {
+        let matches = fuzzy::top_k_boosted(&self.query(), &self.candidates, &self.boosts, self.candidates.len());
+        self.ranked = matches.iter().map(|m| self.candidates[m.index].clone()).collect();
+        self.positions = matches.into_iter().map(|m| m.positions).collect();
+        self.highlighted = None;
+        self.error = None;
+    }
+
+    // "\3" being typed and "@-5m" are not part of the name that is searched
+    fn query(&self) -> String {
+        let mut text = self.input.text();
+        if let Some((before, number)) = text.rsplit_once('\\')
+            && number.chars().all(|c| c.is_ascii_digit()) {
+            text = before;
+        }
+        time_spec::split_inline(text).0
+    }
+
+    // "\12" at the end of the input and the text typed before it. Without candidates
+    // the prompt is free text, like a description, and "C:\2024" is kept as typed
+    fn label(&self) -> Option<(&str, &str)> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+        let (typed, number) = self.input.text().rsplit_once('\\')?;
+        (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some((typed, number))
+    }
+
+    fn labelled(&self, number: &str) -> Option<&String> {
+        if number.len() > 1 && number.starts_with('0') {
+            return None;
+        }
+        self.ranked.get(number.parse::<usize>().ok()?)
+    }
+
+    fn missing_label(&self, number: &str) -> String {
+        match self.ranked.len() {
+            0 => format!("Nothing to pick with \\{}", number),
+            count => format!("There is no \\{}, the labels go from \\0 to \\{}", number, count - 1),
+        }
+    }
+
+    // "\3" picks the candidate labelled 3 as soon as no longer label starts with it.
+    // With 25 candidates "\1" waits for a second digit, Enter picks it alone
+    fn quick_select(&mut self) -> Option<Action> {
+        let (typed, number) = self.label()?;
+        let Some(picked) = self.labelled(number) else {
+            self.error = Some(self.missing_label(number));
+            return None;
+        };
+        let index: usize = number.parse().ok()?;
+        if index > 0 && index * 10 < self.ranked.len() {
+            return None;
+        }
+        Some(Action::Submit { input: typed.trim().to_string(), picked: Some(picked.clone()) })
+    }
+}
+
+// Columns of "3:name" as shown in the list
+fn labelled_width(index: usize, item: &str) -> usize {
+    index.to_string().len() + 1 + item.width()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::config::Keybindings;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn new_selector(candidates: &[&str]) -> Selector {
+        let keys = Keys::from_config(&Keybindings::default()).unwrap();
+        Selector::new(candidates.iter().map(|c| c.to_string()).collect(), keys)
+    }
+
+    fn press(selector: &mut Selector, code: KeyCode) -> Action {
+        selector.handle_key(&KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(selector: &mut Selector, text: &str) -> Action {
+        text.chars().map(|c| press(selector, KeyCode::Char(c))).last().unwrap_or(Action::Edit)
+    }
+
+    #[test]
+    fn typing_ranks_and_tab_highlights() {
+        let mut selector = new_selector(&["Diseño data", "Llamadas", "datos viejos", "data"]);
+        type_text(&mut selector, "dat");
+        assert_eq!(selector.ranked, vec!["data", "datos viejos", "Diseño data"]);
+        assert_eq!(selector.highlighted(), None);
+
+        press(&mut selector, KeyCode::Tab);
+        press(&mut selector, KeyCode::Tab);
+        assert_eq!(selector.highlighted(), Some(selector.ranked[1].as_str()));
+        press(&mut selector, KeyCode::BackTab);
+        press(&mut selector, KeyCode::BackTab);
+        assert_eq!(selector.highlighted(), Some(selector.ranked[2].as_str()));
+
+        // Typing again drops the highlight
+        press(&mut selector, KeyCode::Backspace);
+        assert_eq!((selector.input(), selector.highlighted()), ("da", None));
+        assert_eq!(press(&mut selector, KeyCode::Enter), Action::Submit { input: "da".to_string(), picked: None });
+        assert_eq!(press(&mut selector, KeyCode::Esc), Action::Quit);
+    }
+
+    #[test]
+    fn quick_select_picks_by_index_and_ignores_missing_ones() {
+        let mut selector = new_selector(&["data", "Diseño"]);
+        assert_eq!(type_text(&mut selector, "x\\7"), Action::Edit);
+        selector.set_candidates(vec!["fix bug".to_string(), "review".to_string()]);
+        assert_eq!(type_text(&mut selector, "@-5m \\1"),
+            Action::Submit { input: "@-5m".to_string(), picked: Some(selector.ranked[1].clone()) });
+
+        // Nothing to pick from, the keys only edit the input
+        let mut empty = new_selector(&[]);
+        assert_eq!(press(&mut empty, KeyCode::Tab), Action::Edit);
+        assert_eq!(empty.highlighted(), None);
+    }
+
+    #[test]
+    fn free_text_prompts_keep_a_backslash_and_digits() {
+        let mut description = new_selector(&[]);
+        assert_eq!(type_text(&mut description, "copiado a C:\\2024"), Action::Edit);
+        assert_eq!(description.error(), None);
+        assert_eq!(press(&mut description, KeyCode::Enter),
+            Action::Submit { input: "copiado a C:\\2024".to_string(), picked: None });
+    }
+
+    #[test]
+    fn only_matches_are_shown_and_the_suffixes_are_not_searched() {
+        let mut selector = new_selector(&["Llamada", "fix bug", "Logo nuevo"]);
+        type_text(&mut selector, "lgo @-5m");
+        assert_eq!(selector.ranked, vec!["Logo nuevo"]);
+        assert_eq!(selector.positions, vec![vec![0, 2, 3]]);
+        press(&mut selector, KeyCode::Backspace);
+        type_text(&mut selector, "\\");
+        assert_eq!(selector.ranked, vec!["Logo nuevo"]);
+    }
+
+    #[test]
+    fn render_moves_back_to_the_prompt_over_wrapped_candidates() {
+        let selector = new_selector(&["aaaaaaaaaa", "bbbbbbbbbb"]);
+        let mut screen = Vec::new();
+        selector.render(&mut screen, 10).unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        // One candidate per page, the 18 columns of the first one take two rows of 10
+        assert!(screen.contains("> \r\n0:aaaaaaaaaa (1/2)"));
+        assert!(screen.ends_with("\u{1b}[2A\u{1b}[3G"));
+    }
+
+    #[test]
+    fn long_lists_have_pages_and_multi_digit_labels() {
+        let names: Vec<String> = (0..25).map(|i| format!("task {:02}", i)).collect();
+        let mut selector = new_selector(&names.iter().map(String::as_str).collect::<Vec<_>>());
+        // 3 of "0:task 00" or 2 of "10:task 10" fit in 40 columns with the separators and the mark
+        let pages = selector.pages(40);
+        assert_eq!((pages[0].clone(), pages[4].clone(), pages.len()), (0..3, 11..13, 11));
+        selector.render(&mut Vec::new(), 40).unwrap();
+        press(&mut selector, KeyCode::PageDown);
+        assert_eq!(selector.highlighted(), Some("task 03"));
+        let mut screen = Vec::new();
+        selector.render(&mut screen, 40).unwrap();
+        assert!(String::from_utf8(screen).unwrap().contains("(2/11)"));
+        press(&mut selector, KeyCode::PageUp);
+        press(&mut selector, KeyCode::PageUp);
+        assert_eq!(selector.highlighted(), Some("task 00"));
+
+        // "\\1" could be the start of "\\12", "\\3" can't be the start of anything
+        assert_eq!(type_text(&mut selector, "\\1"), Action::Edit);
+        assert_eq!(type_text(&mut selector, "2"), Action::Submit { input: String::new(), picked: Some("task 12".to_string()) });
+        selector.set_input("");
+        type_text(&mut selector, "\\1");
+        assert_eq!(press(&mut selector, KeyCode::Enter), Action::Submit { input: String::new(), picked: Some("task 01".to_string()) });
+        selector.set_input("");
+        assert_eq!(type_text(&mut selector, "\\3"), Action::Submit { input: String::new(), picked: Some("task 03".to_string()) });
+
+        // Out of range the input stays and the error is shown below the candidates
+        selector.set_input("");
+        assert_eq!(type_text(&mut selector, "\\30"), Action::Edit);
+        assert_eq!(selector.error(), Some("There is no \\30, the labels go from \\0 to \\24"));
+        let mut screen = Vec::new();
+        selector.render(&mut screen, 80).unwrap();
+        assert!(String::from_utf8(screen).unwrap().contains("\r\nThere is no \\30"));
+        assert_eq!(press(&mut selector, KeyCode::Enter), Action::Edit);
+        press(&mut selector, KeyCode::Backspace);
+        assert_eq!(selector.error(), None);
+    }
+
+    #[test]
+    fn every_candidate_can_be_reached() {
+        let names: Vec<String> = (0..150).map(|i| format!("project {:03}", i)).collect();
+        let mut selector = new_selector(&names.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(selector.ranked().len(), 150);
+        let pages = selector.pages(80);
+        assert_eq!(pages.last().map(|page| page.end), Some(150));
+        assert_eq!(type_text(&mut selector, "\\149"), Action::Submit { input: String::new(), picked: Some("project 149".to_string()) });
+    }
+}
@@ -0,0 +1,120 @@
+use std::io::{self, Write};
+use std::time::Duration;
+use chrono::{NaiveDate, TimeDelta};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType, disable_raw_mode, enable_raw_mode};
+use crossterm::{cursor, queue};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use core::clock::Clock;
+use core::config::Config;
+use core::data_managing::Storage;
+use core::data_managing::day_log::Entry;
+use crate::errors;
+use crate::keys::Keys;
+use crate::timer::{EXIT_ERROR, EXIT_NOTHING_RUNNING, EXIT_OK};
+
+// "Diseño / Logo since 09:30  1:15:09", the date is added when it started another day
+pub fn running_line(clock: &dyn Clock, config: &Config, running: Option<&(NaiveDate, Entry)>) -> String {
+    let Some((date, open)) = running else {
+        return "Nothing running".to_string();
+    };
+    let since = if *date == clock.today() {
+        open.start.format(&config.time_format).to_string()
+    } else {
+        format!("{} {}", date.format(&config.date_format), open.start.format(&config.time_format))
+    };
+    format!("{} / {} since {}  {}", open.project, open.task, since, format_elapsed(clock.now() - date.and_time(open.start)))
+}
+
+// H:MM:SS, so the counter moves every second
+pub fn format_elapsed(elapsed: TimeDelta) -> String {
+    let seconds = elapsed.num_seconds().max(0);
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+// Long lines are cut so the line never wraps and can be redrawn in place.
+// Wide characters like emojis take two columns
+pub fn fit(line: &str, columns: u16) -> String {
+    let mut width = 0;
+    line.graphemes(true)
+        .take_while(|grapheme| {
+            width += grapheme.width();
+            width < usize::from(columns)
+        })
+        .collect()
+}
+
+// Some pseudo terminals report a width of 0, that is taken as unknown
+pub fn terminal_columns() -> u16 {
+    terminal::size().ok().map(|(columns, _)| columns).filter(|columns| *columns > 0).unwrap_or(80)
+}
+
+pub fn show(storage: &dyn Storage, config: &Config) -> i32 {
+    match storage.running_session() {
+        Ok(running) => {
+            println!("{}", running_line(storage.clock(), config, running.as_ref()));
+            if running.is_some() { EXIT_OK } else { EXIT_NOTHING_RUNNING }
+        },
+        Err(e) => {
+            eprintln!("Failed to read the running entry: {}", errors::describe(&e));
+            EXIT_ERROR
+        }
+    }
+}
+
+// Redraws the status line every second until the quit key, q or Ctrl-C
+pub fn watch(storage: &dyn Storage, config: &Config, keys: &Keys) -> i32 {
+    if let Err(e) = enable_raw_mode() {
+        eprintln!("Failed to prepare the terminal: {}", e);
+        return EXIT_ERROR;
+    }
+    let result = watch_loop(storage, config, keys);
+    let _ = disable_raw_mode();
+    println!();
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("Failed to draw the status: {}", e);
+            EXIT_ERROR
+        }
+    }
+}
+
+fn watch_loop(storage: &dyn Storage, config: &Config, keys: &Keys) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    loop {
+        // Read every time, the entry can be stopped from another terminal
+        let line = match storage.running_session() {
+            Ok(running) => running_line(storage.clock(), config, running.as_ref()),
+            Err(e) => errors::describe(&e),
+        };
+        queue!(stdout, cursor::MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+        write!(stdout, "{}", fit(&line, terminal_columns()))?;
+        stdout.flush()?;
+
+        if !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if keys.quit.matches(&key) => return Ok(()),
+            Event::Key(key) if key.code == KeyCode::Char('q') => return Ok(()),
+            Event::Key(key) if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            // A resize only needs the line drawn again with the new width
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_cuts_by_display_width() {
+        assert_eq!(fit("Diseño / Logo", 8), "Diseño ");
+        assert_eq!(fit("🎨🎨🎨 Logo", 6), "🎨🎨");
+        assert_eq!(fit("🎨🎨🎨 Logo", 7), "🎨🎨🎨");
+        assert_eq!(fit("corto", 80), "corto");
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{NaiveDate, NaiveTime};
+use std::fs;
+use std::path::Path;
+use core::clock::Clock;
+use core::data_managing::{day_log, Storage};
+use core::data_managing::day_log::Entry;
+use core::data_managing::error::StorageError;
+use core::{history, time_spec};
+use core::config::Config;
+use std::io::{self, BufRead, Write};
+use crate::errors;
+
+// Exit codes of the non-interactive commands, so scripts and keybindings can tell what happened
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_NOTHING_RUNNING: i32 = 3;
+pub const EXIT_ALREADY_RUNNING: i32 = 4;
+// --ago/--at in the future, before midnight or overlapping another entry
+pub const EXIT_INVALID_TIME: i32 = 5;
+// There is no previous activity to repeat or cancelled entry to restore
+pub const EXIT_NO_HISTORY: i32 = 6;
+
+// Turns --ago and --at into the time to use, None means now
+pub fn resolve_time(clock: &dyn Clock, ago: Option<&str>, at: Option<&str>) -> Result<Option<NaiveTime>, i32> {
+    let spec = time_spec::from_flags(ago, at).map_err(|e| {
+        eprintln!("{:#}", e);
+        EXIT_INVALID_TIME
+    })?;
+    match spec.map(|spec| spec.resolve(clock.current_minute())) {
+        Some(Ok(time)) => Ok(Some(time)),
+        Some(Err(e)) => {
+            eprintln!("{:#}", e);
+            Err(EXIT_INVALID_TIME)
+        },
+        None => Ok(None),
+    }
+}
+
+// "@-5m" or "@09:40" at the end of a prompt changes when the entry starts or ends
+pub fn prompt_time(clock: &dyn Clock, input: &str) -> Result<(String, NaiveTime), String> {
+    let (text, spec) = time_spec::split_inline(input);
+    let time = match spec {
+        Some(spec) => spec.resolve(clock.current_minute()).map_err(|e| e.to_string())?,
+        None => clock.current_minute(),
+    };
+    Ok((text, time))
+}
+
+// What a failed timer operation exits with. The typed errors of core are the
+// expected ones and are shown as they are, the rest with what was being done
+fn fail(doing: &str, error: &anyhow::Error) -> i32 {
+    let code = match error.downcast_ref::<StorageError>() {
+        Some(StorageError::AlreadyRunning { .. }) => EXIT_ALREADY_RUNNING,
+        Some(StorageError::NothingRunning) => EXIT_NOTHING_RUNNING,
+        Some(StorageError::InvalidTime(_)) => EXIT_INVALID_TIME,
+        _ => EXIT_ERROR,
+    };
+    if code == EXIT_ERROR {
+        eprintln!("Failed to {}: {}", doing, errors::describe(error));
+    } else {
+        eprintln!("{}", errors::describe(error));
+    }
+    code
+}
+
+// Creates the project and the task when they are new, like the interactive prompt does
+pub fn ensure_project_task(storage: &dyn Storage, project: &str, task: &str) -> anyhow::Result<()> {
+    if !storage.get_projects()?.iter().any(|p| p == project) {
+        storage.create_project(project)?;
+    }
+    if !storage.get_tasks(project)?.iter().any(|t| t == task) {
+        storage.create_task(project, task)?;
+    }
+    Ok(())
+}
+
+// The project and the task are only created once the entry could start
+pub fn start(storage: &dyn Storage, config: &Config, project: &str, task: &str, at: Option<NaiveTime>) -> i32 {
+    let start = at.unwrap_or_else(|| storage.clock().current_minute());
+    if let Err(e) = storage.init().and_then(|_| storage.start_timer_at(project, task, start)) {
+        return fail("start the timer", &e);
+    }
+    println!("Started {} / {} at {}", project, task, start.format(&config.time_format));
+    if let Err(e) = ensure_project_task(storage, project, task) {
+        eprintln!("Failed to add {} / {} to the projects: {}", project, task, errors::describe(&e));
+        return EXIT_ERROR;
+    }
+    EXIT_OK
+}
+
+pub fn stop(storage: &dyn Storage, config: &Config, description: &str, at: Option<NaiveTime>) -> i32 {
+    let end = at.unwrap_or_else(|| storage.clock().current_minute());
+    match storage.stop_timer_at(description, end) {
+        Ok((date, open)) => {
+            if date == storage.clock().today() {
+                println!("Stopped {} / {} at {}", open.project, open.task, end.format(&config.time_format));
+            } else {
+                println!("Stopped {} / {} at {}, started on {} at {}",
+                    open.project, open.task, end.format(&config.time_format), date.format(&config.date_format), open.start.format(&config.time_format));
+            }
+            EXIT_OK
+        },
+        Err(e) => fail("stop the timer", &e),
+    }
+}
+
+// Stops the running entry and starts the next one at the same time
+pub fn switch(storage: &dyn Storage, config: &Config, project: &str, task: &str, description: &str, at: Option<NaiveTime>) -> i32 {
+    let at = Some(at.unwrap_or_else(|| storage.clock().current_minute()));
+    match stop(storage, config, description, at) {
+        EXIT_OK => start(storage, config, project, task, at),
+        code => code,
+    }
+}
+
+// Where the last cancelled entry is kept, inside the data folder
+pub const CANCEL_BACKUP: &str = ".cancelled";
+
+// The backup is the date of the day file and the entry as it was written there
+fn save_cancelled(backup: &Path, date: NaiveDate, entry: &Entry) -> std::io::Result<()> {
+    fs::write(backup, format!("{}\n{}", date.format("%d-%m-%Y"), entry))
+}
+
+fn read_cancelled(backup: &Path) -> anyhow::Result<(NaiveDate, Entry)> {
+    let content = fs::read_to_string(backup)?;
+    let (date, line) = content.split_once('\n')
+        .ok_or_else(|| anyhow::anyhow!("Malformed backup: {}", backup.display()))?;
+    let date = NaiveDate::parse_from_str(date, "%d-%m-%Y")?;
+    let entry = day_log::parse_day_log(line)?.entries.pop()
+        .ok_or_else(|| anyhow::anyhow!("Malformed backup: {}", backup.display()))?;
+    Ok((date, entry))
+}
+
+// Removes the running entry, keeping a copy in `backup` so it can be undone
+pub fn cancel(storage: &dyn Storage, config: &Config, backup: &Path) -> i32 {
+    match storage.cancel_timer() {
+        Ok((date, cancelled)) => {
+            if let Err(e) = save_cancelled(backup, date, &cancelled) {
+                eprintln!("Failed to keep a backup of the cancelled entry: {}", e);
+            }
+            println!("Cancelled {} / {} started at {} (undo with cancel --undo)", cancelled.project, cancelled.task, cancelled.start.format(&config.time_format));
+            EXIT_OK
+        },
+        Err(e) => fail("cancel the timer", &e),
+    }
+}
+
+// Puts the last cancelled entry back as the running entry of its day
+pub fn undo_cancel(storage: &dyn Storage, config: &Config, backup: &Path) -> i32 {
+    if !backup.exists() {
+        eprintln!("There is no cancelled entry to restore");
+        return EXIT_NO_HISTORY;
+    }
+    let (date, entry) = match read_cancelled(backup) {
+        Ok(cancelled) => cancelled,
+        Err(e) => {
+            eprintln!("Failed to read the cancelled entry: {}", errors::describe(&e));
+            return EXIT_ERROR;
+        }
+    };
+    let mut day = match storage.read_day(date) {
+        Ok(day) => day,
+        Err(e) => {
+            eprintln!("Failed to read the entries of {}: {}", date, errors::describe(&e));
+            return EXIT_ERROR;
+        }
+    };
+    if let Err(e) = day.check_start(entry.start) {
+        return fail("restore the cancelled entry", &e.into());
+    }
+    day.entries.push(entry.clone());
+    if let Err(e) = storage.write_day(date, &day) {
+        eprintln!("Failed to restore the cancelled entry: {}", errors::describe(&e));
+        return EXIT_ERROR;
+    }
+    let _ = fs::remove_file(backup);
+    println!("Restored {} / {} started at {}", entry.project, entry.task, entry.start.format(&config.time_format));
+    EXIT_OK
+}
+
+// Starts the last closed activity again, or lets the user pick one of the last `pick`
+pub fn again(storage: &dyn Storage, config: &Config, pick: Option<usize>, at: Option<NaiveTime>) -> i32 {
+    let activities = match history::recent_activities(storage, pick.unwrap_or(1).max(1)) {
+        Ok(activities) => activities,
+        Err(e) => {
+            eprintln!("Failed to read previous entries: {}", errors::describe(&e));
+            return EXIT_ERROR;
+        }
+    };
+    if activities.is_empty() {
+        eprintln!("There is no previous activity to repeat");
+        return EXIT_NO_HISTORY;
+    }
+
+    let chosen = if pick.is_some() {
+        for (i, activity) in activities.iter().enumerate() {
+            println!("{:>3}  {} / {}", i + 1, activity.project, activity.task);
+        }
+        print!("> ");
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer).is_err() {
+            return EXIT_ERROR;
+        }
+        match answer.trim().parse::<usize>() {
+            Ok(n) if n >= 1 && n <= activities.len() => &activities[n - 1],
+            _ => {
+                eprintln!("'{}' is not a number between 1 and {}", answer.trim(), activities.len());
+                return EXIT_ERROR;
+            }
+        }
+    } else {
+        &activities[0]
+    };
+    start(storage, config, &chosen.project, &chosen.task, at)
+}
@@ -0,0 +1,429 @@
+use std::io;
+use std::time::Duration;
+use chrono::NaiveDate;
+use crossterm::event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use core::config::Config;
+use core::data_managing::Storage;
+use core::data_managing::day_log::{DayLog, Entry};
+use core::data_managing::error::StorageError;
+use core::history;
+use core::report::{self, format_minutes, Report};
+use crate::errors;
+use crate::keys::Keys;
+use crate::selector::{Action, Selector};
+use crate::status;
+use crate::timer::{self, EXIT_ERROR, EXIT_OK};
+
+// Where the keys go
+enum Mode {
+    // One letter commands, see HELP
+    Normal,
+    // `switch` is the description of the entry that stops when the new one starts
+    Project { switch: Option<String> },
+    Task { project: String, switch: Option<String> },
+    Describe(Describe),
+}
+
+// What the typed description is for
+enum Describe {
+    Stop,
+    Switch,
+    // Index in today's entries
+    Edit(usize),
+}
+
+const HELP: &str = "s start  x stop  w switch  e edit  ↑↓ select  q quit";
+
+// Everything the dashboard shows, read again after each change
+pub struct App<'a> {
+    storage: &'a dyn Storage,
+    config: &'a Config,
+    keys: Keys,
+    mode: Mode,
+    selector: Selector,
+    running: Option<(NaiveDate, Entry)>,
+    today: DayLog,
+    week: Option<Report>,
+    selected: ListState,
+    message: String,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(storage: &'a dyn Storage, config: &'a Config, keys: Keys) -> App<'a> {
+        let mut app = App {
+            storage,
+            config,
+            keys,
+            mode: Mode::Normal,
+            selector: Selector::new(Vec::new(), keys),
+            running: None,
+            today: DayLog::default(),
+            week: None,
+            selected: ListState::default(),
+            message: String::new(),
+            quit: false,
+        };
+        app.refresh();
+        app
+    }
+
+    fn refresh(&mut self) {
+        let today = self.storage.clock().today();
+        let (year, week) = report::current_week(today, self.config.week_start);
+        let read = self.storage.running_session().and_then(|running| {
+            Ok((running, self.storage.read_day(today)?, report::week_report(self.storage, year, week, self.config.week_start)?))
+        });
+        match read {
+            Ok((running, day, week)) => {
+                self.running = running;
+                self.today = day;
+                self.week = Some(week);
+            },
+            Err(e) => self.message = errors::describe(&e),
+        }
+        let last = self.today.entries.len().checked_sub(1);
+        match self.selected.selected() {
+            Some(index) if Some(index) <= last => {},
+            _ => self.selected.select(last),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent) {
+        if matches!(self.mode, Mode::Normal) {
+            self.handle_command(key);
+            return;
+        }
+        match self.selector.handle_key(key) {
+            Action::Edit => {},
+            Action::Quit => {
+                self.mode = Mode::Normal;
+                self.message = "Cancelled".to_string();
+            },
+            Action::Submit { input, picked } => self.submit(input, picked),
+        }
+    }
+
+    // Only the prompts take pasted text, in Normal mode keys are commands
+    pub fn paste(&mut self, text: &str) {
+        if !matches!(self.mode, Mode::Normal) {
+            self.selector.paste(text);
+        }
+    }
+
+    fn handle_command(&mut self, key: &KeyEvent) {
+        self.message.clear();
+        match key.code {
+            _ if self.keys.quit.matches(key) => self.quit = true,
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('s') => match &self.running {
+                Some((_, open)) => self.message = format!("{} / {} is running, stop or switch it first", open.project, open.task),
+                None => self.pick_project(None),
+            },
+            KeyCode::Char('x') | KeyCode::Char('w') if self.running.is_none() => {
+                self.message = "There is no running entry".to_string();
+            },
+            KeyCode::Char('x') => self.describe(Describe::Stop, ""),
+            KeyCode::Char('w') => self.describe(Describe::Switch, ""),
+            KeyCode::Char('e') => match self.selected.selected().map(|index| (index, &self.today.entries[index])) {
+                Some((_, entry)) if entry.is_open() => self.message = "Stop the entry before describing it".to_string(),
+                Some((index, entry)) => {
+                    let description = entry.description.clone().unwrap_or_default();
+                    self.describe(Describe::Edit(index), &description);
+                },
+                None => self.message = "There are no entries today".to_string(),
+            },
+            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+            KeyCode::Down | KeyCode::Char('j')
+                if self.selected.selected().is_some_and(|index| index + 1 < self.today.entries.len()) => {
+                self.selected.select_next();
+            },
+            _ => {},
+        }
+    }
+
+    fn pick_project(&mut self, switch: Option<String>) {
+        match self.storage.get_projects() {
+            Ok(projects) => {
+                let frecency = history::frecency(self.storage).unwrap_or_default();
+                let boosts = projects.iter().map(|project| frecency.project(project)).collect();
+                self.selector.set_boosted_candidates(projects, boosts);
+                self.mode = Mode::Project { switch };
+            },
+            Err(e) => self.message = errors::describe(&e),
+        }
+    }
+
+    fn describe(&mut self, describe: Describe, description: &str) {
+        self.selector.set_candidates(Vec::new());
+        self.selector.set_input(description);
+        self.mode = Mode::Describe(describe);
+    }
+
+    fn submit(&mut self, input: String, picked: Option<String>) {
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => {},
+            Mode::Project { switch } => {
+                let (project, created) = match picked {
+                    Some(project) => (project, Ok(())),
+                    None if input.is_empty() => {
+                        self.mode = Mode::Project { switch };
+                        return;
+                    },
+                    // Typing the name of a project that exists picks it
+                    None => match self.storage.create_project(&input) {
+                        Err(e) if matches!(e.downcast_ref(), Some(StorageError::AlreadyExists(_))) => (input, Ok(())),
+                        created => (input, created),
+                    },
+                };
+                let tasks = created.and_then(|_| self.storage.get_tasks(&project));
+                match tasks {
+                    Ok(tasks) => {
+                        let frecency = history::frecency(self.storage).unwrap_or_default();
+                        let boosts = tasks.iter().map(|task| frecency.task(&project, task)).collect();
+                        self.selector.set_boosted_candidates(tasks, boosts);
+                        self.mode = Mode::Task { project, switch };
+                    },
+                    Err(e) => self.message = errors::describe(&e),
+                }
+            },
+            Mode::Task { project, switch } => {
+                let started = timer::prompt_time(self.storage.clock(), &input).and_then(|(typed, at)| {
+                    let task = picked.unwrap_or(typed);
+                    if task.is_empty() {
+                        return Err("Type or pick a task".to_string());
+                    }
+                    let result = timer::ensure_project_task(self.storage, &project, &task)
+                        .and_then(|_| match &switch {
+                            Some(description) => self.storage.stop_timer_at(description, at).map(|_| ()),
+                            None => Ok(()),
+                        })
+                        .and_then(|_| self.storage.start_timer_at(&project, &task, at));
+                    result.map(|_| format!("Started {} / {} at {}", project, task, at.format(&self.config.time_format)))
+                        .map_err(|e| errors::describe(&e))
+                });
+                self.message = started.unwrap_or_else(|e| e);
+            },
+            Mode::Describe(Describe::Stop) => {
+                let stopped = timer::prompt_time(self.storage.clock(), &input).and_then(|(description, at)| {
+                    self.storage.stop_timer_at(&description, at)
+                        .map(|_| format!("Stopped at {}", at.format(&self.config.time_format)))
+                        .map_err(|e| errors::describe(&e))
+                });
+                self.message = stopped.unwrap_or_else(|e| e);
+            },
+            Mode::Describe(Describe::Switch) => self.pick_project(Some(input)),
+            Mode::Describe(Describe::Edit(index)) => {
+                let today = self.storage.clock().today();
+                let mut day = self.today.clone();
+                day.entries[index].description = Some(input).filter(|input| !input.is_empty());
+                self.message = match self.storage.write_day(today, &day) {
+                    Ok(()) => "Description saved".to_string(),
+                    Err(e) => errors::describe(&e),
+                };
+            },
+        }
+        self.refresh();
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [running, middle, bottom] = Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)])
+            .areas(frame.area());
+        let [entries, side] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(middle);
+        let [selector, week] = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(side);
+
+        frame.render_widget(Paragraph::new(status::running_line(self.storage.clock(), self.config, self.running.as_ref())).block(Block::default().borders(Borders::ALL).title("Running")), running);
+        self.draw_entries(frame, entries);
+        self.draw_selector(frame, selector);
+        self.draw_week(frame, week);
+        self.draw_prompt(frame, bottom);
+    }
+
+    fn draw_entries(&mut self, frame: &mut Frame, area: Rect) {
+        let time_format = &self.config.time_format;
+        let items: Vec<ListItem> = self.today.entries.iter()
+            .map(|entry| {
+                let end = entry.end.map(|end| end.format(time_format).to_string()).unwrap_or_default();
+                let minutes = entry.duration().map(|d| format_minutes(d.num_minutes())).unwrap_or_default();
+                ListItem::new(format!("{}-{:<5}  {:>5}  {} / {}  {}",
+                    entry.start.format(time_format), end, minutes, entry.project, entry.task,
+                    entry.description.as_deref().unwrap_or("")))
+            })
+            .collect();
+        let title = format!("Today {}", self.storage.clock().today().format(&self.config.date_format));
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.selected);
+    }
+
+    fn draw_selector(&self, frame: &mut Frame, area: Rect) {
+        let title = match &self.mode {
+            Mode::Project { .. } => "Project".to_string(),
+            Mode::Task { project, .. } => format!("Task of {}", project),
+            _ => "Projects".to_string(),
+        };
+        let candidates = match self.mode {
+            Mode::Project { .. } | Mode::Task { .. } => self.selector.ranked(),
+            _ => &[],
+        };
+        // The "\N" label of each one and the matched characters in bold
+        let items: Vec<ListItem> = candidates.iter().zip(self.selector.positions()).enumerate()
+            .map(|(index, (candidate, positions))| {
+                let label = Span::styled(format!("{:>2} ", index), Style::default().add_modifier(Modifier::DIM));
+                let spans: Vec<Span> = std::iter::once(label).chain(candidate.chars().enumerate()
+                    .map(|(i, c)| if positions.contains(&i) {
+                        Span::styled(c.to_string(), Style::default().add_modifier(Modifier::BOLD))
+                    } else {
+                        Span::raw(c.to_string())
+                    }))
+                    .collect();
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let mut state = ListState::default();
+        state.select(self.selector.highlighted().and_then(|h| candidates.iter().position(|c| c == h)));
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_week(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = Vec::new();
+        let mut title = "Week".to_string();
+        if let Some(week) = &self.week {
+            title = format!("Week {} - {}  {}", week.from.format(&self.config.date_format), week.to.format(&self.config.date_format), format_minutes(week.minutes));
+            for project in &week.projects {
+                lines.push(Line::from(format!("{:<20} {:>6}", project.project, format_minutes(project.minutes))));
+                for task in &project.tasks {
+                    lines.push(Line::from(format!("  {:<18} {:>6}", task.task, format_minutes(task.minutes))));
+                }
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), area);
+    }
+
+    fn draw_prompt(&self, frame: &mut Frame, area: Rect) {
+        let title = match &self.mode {
+            Mode::Normal => self.message.as_str(),
+            _ if self.selector.error().is_some() => self.selector.error().unwrap_or_default(),
+            Mode::Project { .. } => "Project, Tab or \\N to pick, Enter to confirm",
+            Mode::Task { .. } => "Task, \"@-5m\" or \"@09:40\" to change the start",
+            Mode::Describe(Describe::Edit(_)) => "Description",
+            Mode::Describe(_) => "Description of the entry being stopped",
+        };
+        let block = Block::default().borders(Borders::ALL).title(title.to_string());
+        if matches!(self.mode, Mode::Normal) {
+            frame.render_widget(Paragraph::new(HELP).block(block), area);
+            return;
+        }
+        let input = self.selector.input();
+        frame.render_widget(Paragraph::new(format!("> {}", input)).block(block), area);
+        frame.set_cursor_position((area.x + 3 + self.selector.cursor_width() as u16, area.y + 1));
+    }
+}
+
+// Full screen dashboard until the user quits
+pub fn run(storage: &dyn Storage, config: &Config, keys: Keys) -> i32 {
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("Failed to open the dashboard: {}", e);
+            return EXIT_ERROR;
+        }
+    };
+    // Pasted text arrives in one event instead of as keys
+    let _ = crossterm::execute!(io::stdout(), EnableBracketedPaste);
+    let result = run_app(&mut terminal, App::new(storage, config, keys));
+    let _ = crossterm::execute!(io::stdout(), DisableBracketedPaste);
+    let _ = ratatui::try_restore();
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("Failed to draw the dashboard: {}", e);
+            EXIT_ERROR
+        }
+    }
+}
+
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        // Without keys it still redraws every second so the elapsed time moves
+        if !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => app.handle_key(&key),
+            Event::Paste(text) => app.paste(&text),
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+    use crossterm::event::KeyModifiers;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use core::clock::FixedClock;
+    use core::config::Keybindings;
+    use core::data_managing::memory_storage::MemoryStorage;
+
+    fn type_keys(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\r' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            app.handle_key(&KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn screen(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn start_switch_stop_and_edit_from_the_dashboard() {
+        let now = NaiveDateTime::parse_from_str("2026-01-05 09:30", "%Y-%m-%d %H:%M").unwrap();
+        let clock = Rc::new(FixedClock::new(now));
+        let storage = MemoryStorage::new().with_clock(clock.clone());
+        let config = Config::default();
+        let keys = Keys::from_config(&Keybindings::default()).unwrap();
+        let mut app = App::new(&storage, &config, keys);
+
+        type_keys(&mut app, "sDiseño\rLogo\r");
+        clock.advance(TimeDelta::minutes(75));
+        assert!(screen(&mut app).contains("Diseño / Logo since 09:30  1:15:00"));
+
+        type_keys(&mut app, "wboceto\rDiseño\r\t\r");
+        clock.advance(TimeDelta::minutes(15));
+        type_keys(&mut app, "xlisto\r");
+        let day = storage.read_day(now.date()).unwrap();
+        assert_eq!(day.entries.len(), 2);
+        assert_eq!(day.entries[0].end, Some(NaiveTime::from_hms_opt(10, 45, 0).unwrap()));
+        assert_eq!((day.entries[1].task.as_str(), day.entries[1].description.as_deref()), ("Logo", Some("listo")));
+
+        type_keys(&mut app, "ke!\r");
+        assert_eq!(storage.read_day(now.date()).unwrap().entries[0].description.as_deref(), Some("boceto!"));
+        assert!(screen(&mut app).contains("Nothing running"));
+    }
+}
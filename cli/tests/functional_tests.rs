@@ -1,4 +1,4 @@
-use std::{path::PathBuf, thread, time};
+use std::{fs, path::PathBuf, process::Command, thread, time};
 use anyhow::{Ok, Result};
 use rexpect::spawn;
 use tempfile::tempdir;
@@ -6,6 +6,7 @@ use tempfile::tempdir;
 
 
 #[test]
+#[allow(clippy::needless_return)]
 fn open_close() -> Result<()> {
     // Create the isolated temporary directory
     let temp_dir = tempdir()?;
@@ -33,6 +34,7 @@ fn open_close() -> Result<()> {
 }
 
 #[test]
+#[allow(clippy::needless_return)]
 fn open_simple_start() -> Result<()> {
     // Create the isolated temporary directory
     let temp_dir = tempdir()?;
@@ -69,6 +71,283 @@ fn open_simple_start() -> Result<()> {
         Err(anyhow::anyhow!("Required folders not found"))
     }
 }
+#[test]
+fn migrate_names_rewrites_unambiguous_lines() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("Projects"))?;
+    fs::create_dir_all(temp_path.join("Weeks").join("2025 W01"))?;
+    fs::write(temp_path.join("Projects").join("Proyecto testing.txt"), "Task testing\n")?;
+    fs::write(temp_path.join("Projects").join("Proyecto-testing.txt"), "Task testing\n")?;
+    fs::write(temp_path.join("Projects").join("data.txt"), "fix bug\n")?;
+    let day_file = temp_path.join("Weeks").join("2025 W01").join("31-12-2025.txt");
+    fs::write(&day_file, "09:00 data_fix-bug (a) 10:00\n10:00 Proyecto-testing_Task-testing (b) 11:00\n")?;
+
+    let (code, stdout) = run_cli(temp_path, &["migrate-names"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("1 lines could not be mapped"));
+
+    assert_eq!(fs::read_to_string(&day_file)?,
+        "09:00 \"data\" \"fix bug\" (a) 10:00\n10:00 Proyecto-testing_Task-testing (b) 11:00\n");
+    Ok(())
+}
+
+#[test]
+fn migrate_imports_projects_and_weeks_into_the_database() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("Projects"))?;
+    fs::create_dir_all(temp_path.join("Weeks").join("2025 W01"))?;
+    fs::write(temp_path.join("Projects").join("data.txt"), "fix bug\nreview\n")?;
+    fs::write(temp_path.join("Weeks").join("2025 W01").join("31-12-2025.txt"),
+        "09:00 \"data\" \"fix bug\" (a) 10:00\n10:00 \"other\" \"call\" (")?;
+
+    let (code, stdout) = run_cli(temp_path, &["migrate"])?;
+    assert_eq!(code, 0, "{}", stdout);
+    assert!(stdout.contains("Migration completed"));
+    assert!(temp_path.join("db").join("data.mdb").exists());
+
+    // Running it again replaces the content instead of duplicating it
+    assert!(run_cli(temp_path, &["migrate"])?.1.contains("   entries          2          2"));
+    Ok(())
+}
+
+#[test]
+fn report_day_and_week_totals() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let week_path = temp_path.join("Weeks").join("2025 W01");
+    fs::create_dir_all(&week_path)?;
+    fs::write(week_path.join("30-12-2025.txt"), "09:00 \"data\" \"review\" (a) 09:30\n")?;
+    fs::write(week_path.join("31-12-2025.txt"),
+        "09:00 \"data\" \"fix bug\" (a) 10:00\n10:30 \"other, inc\" \"call\" (b) 10:45\n")?;
+
+    let (_, stdout) = run_cli(temp_path, &["report", "day", "2025-12-31", "--format", "csv"])?;
+    assert_eq!(stdout, "kind,date,project,task,from,to,minutes
+task,2025-12-31,data,fix bug,,,60
+project,2025-12-31,data,,,,60
+task,2025-12-31,\"other, inc\",call,,,15
+project,2025-12-31,\"other, inc\",,,,15
+total,2025-12-31,,,,,75
+gap,2025-12-31,,,10:00,10:30,30
+");
+
+    let (_, stdout) = run_cli(temp_path, &["report", "week", "2026-W01", "--format", "json"])?;
+    assert!(stdout.contains("\"from\": \"2025-12-29\""));
+    assert!(stdout.contains("\"minutes\": 105"));
+    Ok(())
+}
+
+// The binary on the data folder, so a config file of the machine running the tests
+// can't change the output
+fn cli(temp_path: &std::path::Path) -> Command {
+    let mut command = Command::new("../target/debug/cli");
+    command.env("XDG_CONFIG_HOME", temp_path.join("no-config"))
+        .arg("--config-path").arg(temp_path);
+    command
+}
+
+fn run_cli(temp_path: &std::path::Path, args: &[&str]) -> Result<(i32, String)> {
+    let output = cli(temp_path)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    Ok((output.status.code().unwrap_or(-1), String::from_utf8(output.stdout)?))
+}
+
+// Runs the binary with the clock stopped at `now`, so the written times are known
+fn run_cli_at(temp_path: &std::path::Path, now: &str, args: &[&str]) -> Result<(i32, String)> {
+    let output = cli(temp_path)
+        .env("TIME_LOGGER_NOW", now)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    Ok((output.status.code().unwrap_or(-1), String::from_utf8(output.stdout)?))
+}
+
+// The day the tests that write entries run on, a Monday
+const TODAY: &str = "2026-01-05";
+
+fn at(time: &str) -> String {
+    format!("{}T{}", TODAY, time)
+}
+
+fn todays_file(temp_path: &std::path::Path) -> Result<String> {
+    Ok(fs::read_to_string(temp_path.join("Weeks").join("2026 W02").join("05-01-2026.txt"))?)
+}
+
+#[test]
+fn non_interactive_start_switch_stop_and_cancel() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["stop"])?.0, 3);
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["start", "--project", "data_pipeline", "--task", "fix-bug"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("09:10"), &["start", "--project", "data_pipeline", "--task", "fix-bug"])?.0, 4);
+    assert_eq!(run_cli_at(temp_path, &at("10:00"), &["switch", "-p", "Otro", "-t", "Llamada", "-d", "primer arreglo"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("10:30"), &["stop", "-d", "hecho"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("10:40"), &["cancel"])?.0, 3);
+    assert_eq!(run_cli_at(temp_path, &at("11:00"), &["start", "-p", "Otro", "-t", "Llamada"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("11:05"), &["cancel"])?.0, 0);
+
+    assert_eq!(todays_file(temp_path)?, "09:00 \"data_pipeline\" \"fix-bug\" (primer arreglo) 10:00\n\
+        10:00 \"Otro\" \"Llamada\" (hecho) 10:30\n");
+    assert_eq!(fs::read_to_string(temp_path.join("Projects").join("Otro.txt"))?, "Llamada\n");
+    Ok(())
+}
+
+#[test]
+fn a_description_with_line_breaks_keeps_the_day_readable() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["start", "-p", "P", "-t", "T"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["stop", "-d", "line one\nline two"])?.0, 0);
+    assert_eq!(todays_file(temp_path)?, "09:00 \"P\" \"T\" (line one\\nline two) 09:30\n");
+    assert_eq!(run_cli_at(temp_path, &at("09:45"), &["status"])?.0, 3);
+    assert_eq!(run_cli_at(temp_path, &at("09:45"), &["start", "-p", "P", "-t", "T"])?.0, 0);
+    Ok(())
+}
+
+#[test]
+fn names_with_slashes_and_line_breaks_start_an_entry() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["start", "-p", "CI/CD", "-t", "a\nb"])?.0, 0);
+    assert_eq!(todays_file(temp_path)?, "09:00 \"CI/CD\" \"a\\nb\" (");
+    assert_eq!(fs::read_to_string(temp_path.join("Projects").join("CI%2FCD.txt"))?, "a\\nb\n");
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["stop"])?.0, 0);
+    Ok(())
+}
+
+#[test]
+fn retroactive_start_and_stop_times() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["start", "-p", "P", "-t", "T", "--at", "23:59", "--ago", "5m"])?.0, 2);
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["start", "-p", "P", "-t", "T", "--at", "00:00"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("09:45"), &["stop", "--ago", "15m"])?.0, 0);
+    // Starting before the previous entry ended is refused
+    assert_eq!(run_cli_at(temp_path, &at("09:45"), &["start", "-p", "P", "-t", "T", "--at", "00:00"])?.0, 5);
+    assert_eq!(todays_file(temp_path)?, "00:00 \"P\" \"T\" () 09:30\n");
+    Ok(())
+}
+
+#[test]
+fn again_repeats_the_last_activity() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["again"])?.0, 6);
+
+    let week_path = temp_path.join("Weeks").join("2025 W01");
+    fs::create_dir_all(&week_path)?;
+    fs::write(week_path.join("30-12-2025.txt"), "09:00 \"A\" \"uno\" (a) 09:30\n")?;
+    fs::write(week_path.join("31-12-2025.txt"),
+        "09:00 \"B\" \"dos\" (b) 10:00\n10:00 \"A\" \"uno\" (c) 11:00\n")?;
+
+    let (code, stdout) = run_cli_at(temp_path, &at("09:00"), &["again"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Started A / uno"));
+    assert_eq!(run_cli_at(temp_path, &at("09:05"), &["cancel"])?.0, 0);
+
+    let mut child = cli(temp_path)
+        .env("TIME_LOGGER_NOW", at("09:05"))
+        .args(["again", "--pick", "5"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    use std::io::Write;
+    child.stdin.take().unwrap().write_all(b"2\n")?;
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("  1  A / uno\n  2  B / dos\n> "));
+    assert!(stdout.contains("Started B / dos"));
+    Ok(())
+}
+
+#[test]
+fn cancel_can_be_undone() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["start", "-p", "P", "-t", "T", "--at", "00:00"])?.0, 0);
+    assert_eq!(todays_file(temp_path)?, "00:00 \"P\" \"T\" (");
+    assert_eq!(run_cli_at(temp_path, &at("09:30"), &["cancel"])?.0, 0);
+    assert_eq!(todays_file(temp_path)?, "");
+    assert!(temp_path.join(".cancelled").exists());
+
+    assert_eq!(run_cli_at(temp_path, &at("09:35"), &["cancel", "--undo"])?.0, 0);
+    assert_eq!(todays_file(temp_path)?, "00:00 \"P\" \"T\" (");
+    assert_eq!(run_cli_at(temp_path, &at("09:35"), &["cancel", "--undo"])?.0, 6);
+
+    // A closed entry is never cancelled
+    assert_eq!(run_cli_at(temp_path, &at("10:00"), &["stop"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, &at("10:00"), &["cancel"])?.0, 3);
+    Ok(())
+}
+
+#[test]
+fn doctor_closes_entries_left_running() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let week_path = temp_path.join("Weeks").join("2026 W01");
+    fs::create_dir_all(&week_path)?;
+    let day_file = week_path.join("30-12-2025.txt");
+    fs::write(&day_file, "09:00 \"A\" \"uno\" (a) 09:30\n10:00 \"B\" \"dos\" (")?;
+
+    let (code, stdout) = run_cli_at(temp_path, &at("09:00"), &["doctor"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Left running: 2025-12-30 B / dos started at 10:00"));
+
+    let output = cli(temp_path)
+        .env("TIME_LOGGER_NOW", at("09:00"))
+        .args(["report", "day"])
+        .output()?;
+    assert!(String::from_utf8(output.stderr)?.contains("1 entries from previous days are still running"));
+
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["doctor", "--fix", "--close-at", "09:00"])?.0, 1);
+    assert_eq!(run_cli_at(temp_path, &at("09:00"), &["doctor", "--fix"])?.0, 0);
+    assert_eq!(fs::read_to_string(&day_file)?, "09:00 \"A\" \"uno\" (a) 09:30\n10:00 \"B\" \"dos\" () 18:00\n");
+    Ok(())
+}
+
+#[test]
+fn a_session_across_midnight_is_not_left_running() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T23:30", &["start", "-p", "P", "-t", "T"])?.0, 0);
+    let (code, stdout) = run_cli_at(temp_path, "2026-01-06T00:45", &["doctor", "--fix"])?;
+    assert_eq!((code, stdout.as_str()), (0, "No entries left running on previous days\n"));
+    let output = cli(temp_path)
+        .env("TIME_LOGGER_NOW", "2026-01-06T00:45")
+        .arg("status")
+        .output()?;
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stderr)?, "");
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T00:45", &["stop"])?.0, 0);
+    Ok(())
+}
+
+#[test]
+fn an_entry_forgotten_yesterday_is_reported_by_doctor() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T10:00", &["start", "-p", "P", "-t", "T"])?.0, 0);
+    let (code, stdout) = run_cli_at(temp_path, "2026-01-06T09:00", &["doctor"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Left running: 2026-01-05 P / T started at 10:00"), "{}", stdout);
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T09:00", &["stop"])?.0, 3);
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T09:00", &["doctor", "--fix"])?.0, 0);
+    assert_eq!(fs::read_to_string(temp_path.join("Weeks").join("2026 W02").join("05-01-2026.txt"))?,
+        "10:00 \"P\" \"T\" () 18:00\n");
+    Ok(())
+}
+
 /* Un test jodido con p.exp_string() y p.send()
 #[test]
 fn test_flujo_interactivo() -> Result<()> {
@@ -101,4 +380,245 @@ fn test_flujo_interactivo() -> Result<()> {
     // Fail the test intentionally so you can read the logs
     return Ok(());
 }
-*/
\ No newline at end of file
+*/
+#[test]
+fn migrate_weeks_moves_late_december_files() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let old_week = temp_path.join("Weeks").join("2025 W01");
+    fs::create_dir_all(&old_week)?;
+    fs::write(old_week.join("30-12-2025.txt"), "09:00 \"data\" \"fix bug\" (a) 10:00\n")?;
+
+    // Misfiled days are still read until they are moved
+    let (code, stdout) = run_cli(temp_path, &["report", "day", "2025-12-30"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("1:00"), "{}", stdout);
+
+    let (code, stdout) = run_cli(temp_path, &["migrate-weeks", "--dry-run"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("Would move"), "{}", stdout);
+    assert!(stdout.contains("1 files would be moved"), "{}", stdout);
+    assert!(old_week.join("30-12-2025.txt").exists());
+
+    let (code, stdout) = run_cli(temp_path, &["migrate-weeks"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("1 files moved"), "{}", stdout);
+    assert!(!old_week.exists());
+    assert!(temp_path.join("Weeks").join("2026 W01").join("30-12-2025.txt").exists());
+    Ok(())
+}
+
+#[test]
+fn reorganize_moves_days_into_monthly_files() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let week_path = temp_path.join("Weeks").join("2026 W02");
+    fs::create_dir_all(&week_path)?;
+    fs::write(week_path.join("05-01-2026.txt"), "09:00 \"A\" \"uno\" (a) 10:00\n")?;
+    fs::write(week_path.join("06-01-2026.txt"), "09:00 \"B\" \"dos\" (b) 09:30\n")?;
+
+    let (code, stdout) = run_cli(temp_path, &["reorganize", "{year}-{month}.txt"])?;
+    assert_eq!(code, 0, "{}", stdout);
+    assert!(stdout.contains("2 days moved from 2 files into 1 files"), "{}", stdout);
+    assert!(!temp_path.join("Weeks").exists());
+    assert_eq!(fs::read_to_string(temp_path.join("2026-01.txt"))?,
+        "# 2026-01-05\n09:00 \"A\" \"uno\" (a) 10:00\n# 2026-01-06\n09:00 \"B\" \"dos\" (b) 09:30\n");
+
+    // Readers follow the new layout
+    let (code, stdout) = run_cli(temp_path, &["report", "week", "2026-W02"])?;
+    assert_eq!(code, 0);
+    assert!(stdout.contains("1:30"), "{}", stdout);
+
+    let (code, _) = run_cli(temp_path, &["reorganize", "{year}/{month}/{date:%Y-%m-%d}.txt"])?;
+    assert_eq!(code, 0);
+    assert!(!temp_path.join("2026-01.txt").exists());
+    assert!(temp_path.join("2026").join("01").join("2026-01-06.txt").exists());
+    Ok(())
+}
+
+#[test]
+fn the_layout_of_config_toml_is_followed_and_checked() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let config_dir = temp_path.join("config").join("time-logger");
+    fs::create_dir_all(&config_dir)?;
+    let data_dir = temp_path.join("mine");
+    let write_config = |layout: &str| fs::write(config_dir.join("config.toml"),
+        format!("data_dir = \"{}\"\nlayout = \"{}\"\n", data_dir.display(), layout));
+
+    let cli = |args: &[&str]| Command::new("../target/debug/cli")
+        .env("XDG_CONFIG_HOME", temp_path.join("config"))
+        .env("TIME_LOGGER_NOW", format!("{} 10:00", TODAY))
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output();
+
+    // An empty data folder takes the layout of the config
+    write_config("{year}-{month}.txt")?;
+    assert!(cli(&["start", "-p", "A", "-t", "uno", "--at", "09:00"])?.status.success());
+    assert!(data_dir.join("2026-01.txt").exists());
+
+    // With files in another layout nothing runs until reorganize moves them
+    write_config("{year}/{date:%Y-%m-%d}.txt")?;
+    let output = cli(&["status"])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("run reorganize"));
+    let stdout = String::from_utf8(cli(&["config", "show"])?.stdout)?;
+    assert!(stdout.contains("layout = {year}/{date:%Y-%m-%d}.txt (the files are still in {year}-{month}.txt"), "{}", stdout);
+
+    // reorganize refuses a layout that is not the one of the config
+    assert!(!cli(&["reorganize", "{year}.txt"])?.status.success());
+    assert!(data_dir.join("2026-01.txt").exists());
+
+    let output = cli(&["reorganize"])?;
+    assert!(output.status.success());
+    assert!(data_dir.join("2026").join("2026-01-05.txt").exists());
+    let stdout = String::from_utf8(cli(&["status"])?.stdout)?;
+    assert!(stdout.contains("A / uno"), "{}", stdout);
+    Ok(())
+}
+
+#[test]
+fn config_file_sets_the_data_folder_and_formats() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let config_dir = temp_path.join("config").join("time-logger");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(config_dir.join("config.toml"), format!(
+        "data_dir = \"{}\"\ntime_format = \"%Hh%M\"\n\n[schedule]\nend = \"17:30\"\n",
+        temp_path.join("mine").display()))?;
+
+    let cli = |args: &[&str]| Command::new("../target/debug/cli")
+        .env("XDG_CONFIG_HOME", temp_path.join("config"))
+        .env("XDG_DATA_HOME", temp_path.join("share"))
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output();
+
+    let output = cli(&["config", "show"])?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(output.status.success());
+    assert!(stdout.contains(&format!("data_dir = {}", temp_path.join("mine").display())), "{}", stdout);
+    assert!(stdout.contains("storage = text"), "{}", stdout);
+    assert!(stdout.contains("schedule.end = 17:30"), "{}", stdout);
+    assert!(stdout.contains("keybindings.quit = esc"), "{}", stdout);
+
+    let output = cli(&["start", "-p", "A", "-t", "uno", "--at", "00:00"])?;
+    assert!(String::from_utf8(output.stdout)?.contains("Started A / uno at 00h00"));
+    assert!(temp_path.join("mine").join("Projects").join("A.txt").exists());
+
+    // --config-path still wins over the config file
+    let output = cli(&["--config-path", temp_path.join("other").to_str().unwrap(), "config", "show"])?;
+    assert!(String::from_utf8(output.stdout)?.contains(&format!("data_dir = {}", temp_path.join("other").display())));
+
+    // Without data_dir the data goes to $XDG_DATA_HOME
+    fs::write(config_dir.join("config.toml"), "")?;
+    let output = cli(&["config", "show"])?;
+    assert!(String::from_utf8(output.stdout)?.contains(&format!("data_dir = {}", temp_path.join("share").join("time-logger").display())));
+
+    fs::write(config_dir.join("config.toml"), "storage = \"paper\"\n")?;
+    assert!(!cli(&["config", "show"])?.status.success());
+    Ok(())
+}
+
+#[test]
+fn balance_reports_overtime_per_week() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let week_path = temp_path.join("Weeks").join("2026 W02");
+    fs::create_dir_all(&week_path)?;
+    fs::write(week_path.join("05-01-2026.txt"), "08:00 \"A\" \"uno\" (a) 17:30\n")?;
+    fs::write(week_path.join("06-01-2026.txt"), "09:00 \"A\" \"uno\" (a) 16:00\n")?;
+
+    let (code, stdout) = run_cli(temp_path, &["balance", "--from", "2026-01-05", "--to", "2026-01-06"])?;
+    assert_eq!(code, 0, "{}", stdout);
+    assert!(stdout.contains("2026-W02      16:00     16:30       +0:30     +0:30"), "{}", stdout);
+    assert!(stdout.contains("2026-01"), "{}", stdout);
+    assert!(stdout.contains("Balance +0:30"), "{}", stdout);
+    Ok(())
+}
+
+#[test]
+fn fixed_clock_writes_exact_times_across_midnight() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T09:30", &["start", "-p", "Diseño", "-t", "Logo"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T11:15", &["stop", "-d", "boceto"])?.0, 0);
+    // Sunday night of W01 to Monday of W02
+    assert_eq!(run_cli_at(temp_path, "2026-01-11T23:30", &["start", "-p", "Diseño", "-t", "Logo"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "2026-01-12 00:15", &["stop", "-d", "deploy"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "ayer", &["stop"])?.0, 1);
+
+    let weeks = temp_path.join("Weeks");
+    assert_eq!(fs::read_to_string(weeks.join("2026 W02").join("05-01-2026.txt"))?,
+        "09:30 \"Diseño\" \"Logo\" (boceto) 11:15\n");
+    assert_eq!(fs::read_to_string(weeks.join("2026 W02").join("11-01-2026.txt"))?,
+        "23:30 \"Diseño\" \"Logo\" (deploy) 23:59:59\n");
+    assert_eq!(fs::read_to_string(weeks.join("2026 W03").join("12-01-2026.txt"))?,
+        "00:00 \"Diseño\" \"Logo\" (deploy) 00:15\n");
+    Ok(())
+}
+
+#[test]
+fn interactive_prompt_starts_and_stops_an_entry() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let day_file = temp_path.join("Weeks").join("2026 W02").join("05-01-2026.txt");
+
+    let mut start = cli(temp_path);
+    start.env("TIME_LOGGER_NOW", "2026-01-05T09:30");
+    let mut p = rexpect::session::spawn_command(start, Some(10_000))?;
+    thread::sleep(time::Duration::from_millis(200));
+    p.send("Diseño\r")?;
+    p.flush()?;
+    p.exp_string("Archivo creado exitosamente.")?;
+    thread::sleep(time::Duration::from_millis(200));
+    p.send("Logo\r")?;
+    p.flush()?;
+    p.exp_eof()?;
+    assert_eq!(fs::read_to_string(&day_file)?, "09:30 \"Diseño\" \"Logo\" (");
+
+    let mut stop = cli(temp_path);
+    stop.env("TIME_LOGGER_NOW", "2026-01-05T10:45");
+    let mut p = rexpect::session::spawn_command(stop, Some(10_000))?;
+    p.exp_string("/ Logo since 09:30  1:15:00")?;
+    thread::sleep(time::Duration::from_millis(200));
+    p.send("boceto\r")?;
+    p.flush()?;
+    p.exp_eof()?;
+    assert_eq!(fs::read_to_string(&day_file)?, "09:30 \"Diseño\" \"Logo\" (boceto) 10:45\n");
+    Ok(())
+}
+
+#[test]
+fn status_shows_the_running_entry() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T09:30", &["status"])?, (3, "Nothing running\n".to_string()));
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T09:30", &["start", "-p", "Diseño", "-t", "Logo"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T10:45:20", &["status"])?,
+        (0, "Diseño / Logo since 09:30  1:15:20\n".to_string()));
+    // Left open until the next day it was forgotten, in the evening it goes on after midnight
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T08:00", &["status"])?, (3, "Nothing running\n".to_string()));
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T20:30", &["stop"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "2026-01-05T20:30", &["start", "-p", "Diseño", "-t", "Logo"])?.0, 0);
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T08:00", &["status"])?,
+        (0, "Diseño / Logo since 2026-01-05 20:30  11:30:00\n".to_string()));
+    Ok(())
+}
+
+#[test]
+fn status_joins_the_halves_of_a_session_split_at_midnight() -> Result<()> {
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+    let week_path = temp_path.join("Weeks").join("2026 W02");
+    fs::create_dir_all(&week_path)?;
+    fs::write(week_path.join("05-01-2026.txt"), "23:30 \"P\" \"T\" () 23:59:59\n")?;
+    fs::write(week_path.join("06-01-2026.txt"), "00:00 \"P\" \"T\" (")?;
+
+    assert_eq!(run_cli_at(temp_path, "2026-01-06T00:45", &["status"])?,
+        (0, "P / T since 2026-01-05 23:30  1:15:00\n".to_string()));
+    Ok(())
+}
@@ -0,0 +1,42 @@
+// cargo bench -p core --bench ranking
+// Compares the old Levenshtein ordering with fuzzy::top_k on made up task lists
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use core::{fuzzy, utils};
+
+const QUERIES: [&str; 4] = ["p", "proj", "fix bug", "alpha 12"];
+
+fn tasks(count: usize) -> Vec<String> {
+    let words = ["project", "fix", "bug", "review", "alpha", "Diseño", "meeting", "deploy", "call", "docs"];
+    (0..count)
+        .map(|i| format!("{}-{} {} {}", words[i % words.len()], words[(i / 7) % words.len()], words[(i / 3) % words.len()], i))
+        .collect()
+}
+
+// Average time of one call, run for at least half a second
+fn measure(mut run: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < Duration::from_millis(500) {
+        run();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    println!("{:>8}  {:<10}  {:>14}  {:>14}", "tasks", "query", "order_vector", "top_k(10)");
+    for count in [1_000, 10_000, 50_000] {
+        let candidates = tasks(count);
+        for query in QUERIES {
+            // The old function is quadratic, past 10k tasks it takes too long to be worth measuring
+            let old = if count <= 10_000 {
+                format!("{:?}", measure(|| { black_box(utils::order_vector(black_box(query), &candidates)); }))
+            } else {
+                "-".to_string()
+            };
+            let new = measure(|| { black_box(fuzzy::top_k(black_box(query), &candidates, 10)); });
+            println!("{:>8}  {:<10}  {:>14}  {:>14}", count, query, old, format!("{:?}", new));
+        }
+    }
+}
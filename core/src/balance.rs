@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+use serde::Serialize;
+use crate::config::Schedule;
+use crate::data_managing::Storage;
+use crate::report::current_week;
+
+// Tracked against expected time of a week or a month, in minutes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PeriodBalance {
+    // "2026-W02" or "2026-01"
+    pub period: String,
+    pub expected: i64,
+    pub tracked: i64,
+    // Positive is overtime, negative is undertime
+    pub difference: i64,
+    // Sum of the differences up to this period
+    pub balance: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Balance {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub weeks: Vec<PeriodBalance>,
+    pub months: Vec<PeriodBalance>,
+    // Entries still running are not counted as tracked time
+    pub running: usize,
+}
+
+impl Balance {
+    pub fn total(&self) -> i64 {
+        self.weeks.last().map(|week| week.balance).unwrap_or(0)
+    }
+}
+
+// Both ends included. Weeks are named by the ISO week of their Monday, like the reports
+pub fn balance(storage: &dyn Storage, schedule: &Schedule, week_start: Weekday, from: NaiveDate, to: NaiveDate) -> Result<Balance> {
+    let mut tracked: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    let mut running = 0;
+    for date in storage.days()?.into_iter().filter(|d| *d >= from && *d <= to) {
+        for entry in &storage.read_day(date)?.entries {
+            match entry.duration() {
+                Some(duration) => *tracked.entry(date).or_default() += duration.num_minutes(),
+                None => running += 1,
+            }
+        }
+    }
+
+    let mut weeks: Vec<((i32, u32), i64, i64)> = Vec::new();
+    let mut months: Vec<((i32, u32), i64, i64)> = Vec::new();
+    let mut date = from;
+    while date <= to {
+        let expected = schedule.expected_minutes(date);
+        let worked = tracked.get(&date).copied().unwrap_or(0);
+        add_to_period(&mut weeks, current_week(date, week_start), expected, worked);
+        add_to_period(&mut months, (date.year(), date.month()), expected, worked);
+        date += TimeDelta::days(1);
+    }
+
+    Ok(Balance {
+        from,
+        to,
+        weeks: with_running_balance(weeks, |(year, week)| format!("{}-W{:02}", year, week)),
+        months: with_running_balance(months, |(year, month)| format!("{}-{:02}", year, month)),
+        running,
+    })
+}
+
+// Days come in order, so a new key is always a new last period
+fn add_to_period(periods: &mut Vec<((i32, u32), i64, i64)>, key: (i32, u32), expected: i64, tracked: i64) {
+    match periods.last_mut() {
+        Some((last, period_expected, period_tracked)) if *last == key => {
+            *period_expected += expected;
+            *period_tracked += tracked;
+        },
+        _ => periods.push((key, expected, tracked)),
+    }
+}
+
+fn with_running_balance(periods: Vec<((i32, u32), i64, i64)>, name: impl Fn((i32, u32)) -> String) -> Vec<PeriodBalance> {
+    let mut balance = 0;
+    periods.into_iter()
+        .map(|(key, expected, tracked)| {
+            balance += tracked - expected;
+            PeriodBalance { period: name(key), expected, tracked, difference: tracked - expected, balance }
+        })
+        .collect()
+}
@@ -0,0 +1,69 @@
+use std::cell::Cell;
+use std::fmt;
+use anyhow::{Result, bail};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+// Fixes the time seen by the binary, like "2026-01-05T09:30", so tests know what gets written
+pub const NOW_VARIABLE: &str = "TIME_LOGGER_NOW";
+
+// Where "now" comes from. Storages and commands ask the clock instead of calling Local::now()
+pub trait Clock: fmt::Debug {
+    fn now(&self) -> NaiveDateTime;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+
+    // Day files only keep minutes, so every backend stores the same times
+    fn current_minute(&self) -> NaiveTime {
+        let now = self.now().time();
+        NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or(now)
+    }
+}
+
+// The local time of the machine
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+// A clock that only moves when told to, for tests and TIME_LOGGER_NOW
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Cell<NaiveDateTime>,
+}
+
+impl FixedClock {
+    pub fn new(now: NaiveDateTime) -> FixedClock {
+        FixedClock { now: Cell::new(now) }
+    }
+
+    pub fn set(&self, now: NaiveDateTime) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, delta: TimeDelta) {
+        self.now.set(self.now.get() + delta);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.now.get()
+    }
+}
+
+// "2026-01-05T09:30", a space instead of the T and seconds are also accepted
+pub fn parse_now(value: &str) -> Result<NaiveDateTime> {
+    let value = value.trim();
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(now) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(now);
+        }
+    }
+    bail!("Invalid {} '{}', use YYYY-MM-DDTHH:MM", NOW_VARIABLE, value)
+}
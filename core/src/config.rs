@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, bail};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::data_managing::layout::Layout;
+use crate::doctor::DEFAULT_WORK_END;
+
+// Folder name used inside the XDG config and data folders
+pub const APP_NAME: &str = "time-logger";
+pub const CONFIG_FILE: &str = "config.toml";
+
+// Everything that can be set in config.toml, missing keys take the default value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Where projects and days are kept, $XDG_DATA_HOME/time-logger when missing
+    pub data_dir: Option<PathBuf>,
+    // "text" or "lmdb"
+    pub storage: String,
+    // Where the text storage keeps each day, like "{year}/{month}/{date:%Y-%m-%d}.txt".
+    // The data folder remembers the layout its files are in, reorganize moves them when
+    // this one is different. Missing keeps the layout of the data folder
+    pub layout: Option<String>,
+    // How dates and times are shown, the day files always use HH:MM
+    pub date_format: String,
+    pub time_format: String,
+    pub week_start: Weekday,
+    pub schedule: Schedule,
+    pub keybindings: Keybindings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    #[serde(with = "hh_mm")]
+    pub start: NaiveTime,
+    #[serde(with = "hh_mm")]
+    pub end: NaiveTime,
+    // Not counted as work time, like lunch
+    pub break_minutes: i64,
+    pub days: Vec<Weekday>,
+    // Days without expected hours, as "YYYY-MM-DD"
+    pub holidays: Vec<NaiveDate>,
+    pub time_off: Vec<TimeOff>,
+}
+
+// Days off from `from` to `to`, both included. A single day when `to` is missing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeOff {
+    pub from: NaiveDate,
+    pub to: Option<NaiveDate>,
+}
+
+// Keys of the interactive prompt, by crossterm name ("esc", "tab", "ctrl-q"...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keybindings {
+    pub quit: String,
+    pub confirm: String,
+    pub next: String,
+    pub previous: String,
+    // Candidates that don't fit in a line are shown by pages
+    pub page_next: String,
+    pub page_previous: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            data_dir: None,
+            storage: "text".to_string(),
+            layout: None,
+            date_format: "%Y-%m-%d".to_string(),
+            time_format: "%H:%M".to_string(),
+            week_start: Weekday::Mon,
+            schedule: Schedule::default(),
+            keybindings: Keybindings::default(),
+        }
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Schedule {
+        Schedule {
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            end: DEFAULT_WORK_END,
+            break_minutes: 60,
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            holidays: Vec::new(),
+            time_off: Vec::new(),
+        }
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Keybindings {
+        Keybindings {
+            quit: "esc".to_string(),
+            confirm: "enter".to_string(),
+            next: "tab".to_string(),
+            previous: "backtab".to_string(),
+            page_next: "pagedown".to_string(),
+            page_previous: "pageup".to_string(),
+        }
+    }
+}
+
+impl Schedule {
+    // Expected work time of a working day
+    pub fn daily_minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes() - self.break_minutes
+    }
+
+    pub fn is_day_off(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+            || self.time_off.iter().any(|off| date >= off.from && date <= off.to.unwrap_or(off.from))
+    }
+
+    pub fn expected_minutes(&self, date: NaiveDate) -> i64 {
+        if self.days.contains(&date.weekday()) && !self.is_day_off(date) {
+            self.daily_minutes()
+        } else {
+            0
+        }
+    }
+}
+
+impl Config {
+    // A missing file is the default configuration
+    pub fn load(config_file: &Path) -> Result<Config> {
+        if !config_file.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(config_file)
+            .with_context(|| format!("Could not read config file: {}", config_file.display()))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Malformed config file: {}", config_file.display()))?;
+        for (key, format) in [("date_format", &config.date_format), ("time_format", &config.time_format)] {
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                bail!("Invalid {} '{}' in {}", key, format, config_file.display());
+            }
+        }
+        if let Some(layout) = &config.layout {
+            Layout::parse(layout).with_context(|| format!("Invalid layout in {}", config_file.display()))?;
+        }
+        if config.schedule.end <= config.schedule.start {
+            bail!("The schedule in {} ends before it starts", config_file.display());
+        }
+        if config.schedule.break_minutes < 0 || config.schedule.daily_minutes() < 0 {
+            bail!("The break of the schedule in {} is longer than the work day", config_file.display());
+        }
+        Ok(config)
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        match &self.data_dir {
+            Some(path) => expand_home(path),
+            None => default_data_dir(),
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/time-logger/config.toml
+pub fn config_file() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config").join(APP_NAME).join(CONFIG_FILE)
+}
+
+// $XDG_DATA_HOME/time-logger
+pub fn default_data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share").join(APP_NAME)
+}
+
+// The XDG variable when it is an absolute path, else the fallback inside $HOME
+fn xdg_dir(variable: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(variable).map(PathBuf::from) {
+        Some(path) if path.is_absolute() => path,
+        _ => home_dir().join(fallback),
+    }
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."))
+}
+
+// "~/notes" is inside the home folder
+fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+// Schedule times are written as "09:00" like in the day files
+mod hh_mm {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&time, "%H:%M")
+            .map_err(|_| serde::de::Error::custom(format!("'{}' is not a HH:MM time", time)))
+    }
+}
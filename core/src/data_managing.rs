@@ -1 +1,142 @@
-pub mod text_storage;
\ No newline at end of file
+pub mod text_storage;
+pub mod day_log;
+pub mod error;
+pub mod layout;
+pub mod lmdb_storage;
+pub mod memory_storage;
+
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use crate::clock::Clock;
+use day_log::{DayLog, Entry};
+use error::StorageError;
+
+// Only an entry started from this time on can go on after midnight. One started
+// earlier and still open the next day was forgotten
+pub const OVERNIGHT_FROM: NaiveTime = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+
+// Everything the CLI needs from a place where projects, tasks and days are kept.
+// Days are read and written whole, the timer operations are built on top of that.
+// What a timer operation can't do now fails with a StorageError (AlreadyRunning,
+// NothingRunning, InvalidTime) that the caller can downcast
+pub trait Storage {
+    // Prepares whatever the backend needs before the first use
+    fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    // What "today" and "now" are for the timer operations
+    fn clock(&self) -> &dyn Clock;
+
+    fn get_projects(&self) -> Result<Vec<String>>;
+    fn get_tasks(&self, project: &str) -> Result<Vec<String>>;
+    fn create_project(&self, project: &str) -> Result<()>;
+    fn create_task(&self, project: &str, task: &str) -> Result<()>;
+
+    // A day without data is an empty DayLog
+    fn read_day(&self, date: NaiveDate) -> Result<DayLog>;
+    fn write_day(&self, date: NaiveDate, day: &DayLog) -> Result<()>;
+    // Every day with data, oldest first
+    fn days(&self) -> Result<Vec<NaiveDate>>;
+
+    // The running entry and its day. An entry started yesterday evening is still running
+    // after midnight, older ones and the ones forgotten during the day are left for the
+    // doctor command
+    fn running_entry(&self) -> Result<Option<(NaiveDate, Entry)>> {
+        let today = self.clock().today();
+        for date in [today, today - TimeDelta::days(1)] {
+            let day = self.read_day(date)?;
+            if let Some(open) = day.open_entry() {
+                if date < today && open.start < OVERNIGHT_FROM {
+                    break;
+                }
+                return Ok(Some((date, open.clone())));
+            }
+            // Anything written today means yesterday's entry is not running anymore
+            if !day.entries.is_empty() {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    // The running entry as one session, with the day and time it really began. A 00:00
+    // entry that continues the one closed at 23:59:59 the day before began with it.
+    // Only for showing it, the timer operations work on running_entry()
+    fn running_session(&self) -> Result<Option<(NaiveDate, Entry)>> {
+        let Some((mut date, mut open)) = self.running_entry()? else {
+            return Ok(None);
+        };
+        while open.start == NaiveTime::MIN {
+            let previous = date - TimeDelta::days(1);
+            match self.read_day(previous)?.entries.last() {
+                Some(half) if half.continues_next_day() && half.project == open.project && half.task == open.task => {
+                    open.start = half.start;
+                    date = previous;
+                },
+                _ => break,
+            }
+        }
+        Ok(Some((date, open)))
+    }
+
+    fn start_timer_on_task(&self, project: &str, task: &str) -> Result<()> {
+        self.start_timer_at(project, task, self.clock().current_minute())
+    }
+
+    fn start_timer_at(&self, project: &str, task: &str, start: NaiveTime) -> Result<()> {
+        if let Some((_, open)) = self.running_entry()? {
+            return Err(StorageError::AlreadyRunning { project: open.project, task: open.task, start: open.start }.into());
+        }
+        let today = self.clock().today();
+        let mut day = self.read_day(today)?;
+        day.check_start(start)?;
+        day.entries.push(Entry::new(start, project, task));
+        self.write_day(today, &day)
+    }
+
+    fn stop_timer(&self, description: &str) -> Result<(NaiveDate, Entry)> {
+        self.stop_timer_at(description, self.clock().current_minute())
+    }
+
+    // An entry started yesterday is closed at 23:59:59 and continues today from 00:00.
+    // Returns the entry as it was running with its day
+    fn stop_timer_at(&self, description: &str, end: NaiveTime) -> Result<(NaiveDate, Entry)> {
+        let today = self.clock().today();
+        let mut day = self.read_day(today)?;
+        let running = self.running_entry()?;
+        match &running {
+            Some((date, open)) if *date < today => {
+                let mut previous = self.read_day(*date)?;
+                if let Some(first_half) = previous.open_entry_mut() {
+                    first_half.close(day_log::END_OF_DAY, description);
+                }
+                let mut second_half = Entry::new(NaiveTime::MIN, &open.project, &open.task);
+                second_half.close(end, description);
+                day.entries.insert(0, second_half);
+                self.write_day(*date, &previous)?;
+            },
+            _ => {
+                day.check_end(end)?;
+                if let Some(open) = day.open_entry_mut() {
+                    open.close(end, description);
+                }
+            },
+        }
+        self.write_day(today, &day)?;
+        running.ok_or_else(|| StorageError::NothingRunning.into())
+    }
+
+    // Drops the running entry without closing it and returns it with its day
+    fn cancel_timer(&self) -> Result<(NaiveDate, Entry)> {
+        let Some((date, _)) = self.running_entry()? else {
+            return Err(StorageError::NothingRunning.into());
+        };
+        let mut day = self.read_day(date)?;
+        let Some(cancelled) = day.entries.pop_if(|entry| entry.is_open()) else {
+            return Err(StorageError::NothingRunning.into());
+        };
+        self.write_day(date, &day)?;
+        Ok((date, cancelled))
+    }
+}
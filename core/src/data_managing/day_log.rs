@@ -0,0 +1,301 @@
+use std::fmt;
+use chrono::{NaiveTime, TimeDelta, Timelike};
+use crate::data_managing::error::StorageError;
+
+// Format used for the start and end times inside a day file
+pub const TIME_FORMAT: &str = "%H:%M";
+// Only used for END_OF_DAY, the rest of the times are whole minutes
+pub const TIME_FORMAT_SECONDS: &str = "%H:%M:%S";
+
+// An entry that ends here continues at 00:00 of the next day
+pub const END_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+
+// One line of a day file:
+//   closed -> "HH:MM "project" "task" (description) HH:MM\n"
+//   open   -> "HH:MM "project" "task" (" (always the last line, without '\n')
+// Line breaks in the description are written as "\n" (see `escape_line_breaks`)
+// Old files use "project_task" with spaces replaced by '-', those are still read
+// and written back as they were (see `legacy`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub start: NaiveTime,
+    pub end: Option<NaiveTime>,
+    pub project: String,
+    pub task: String,
+    pub description: Option<String>,
+    // The names were read from an old "project_task" line and are kept in that form
+    pub legacy: bool,
+}
+
+impl Entry {
+    pub fn new(start: NaiveTime, project: &str, task: &str) -> Entry {
+        Entry {
+            start,
+            end: None,
+            project: project.to_string(),
+            task: task.to_string(),
+            description: None,
+            legacy: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.end.is_none()
+    }
+
+    pub fn close(&mut self, end: NaiveTime, description: &str) {
+        self.end = Some(end);
+        self.description = Some(description.to_string());
+    }
+
+    // None while the entry is still running
+    pub fn duration(&self) -> Option<TimeDelta> {
+        self.end.map(|end| {
+            // The missing second of END_OF_DAY belongs to this half
+            if end == END_OF_DAY {
+                end - self.start + TimeDelta::seconds(1)
+            } else {
+                end - self.start
+            }
+        })
+    }
+
+    // First half of an entry that went past midnight
+    pub fn continues_next_day(&self) -> bool {
+        self.end == Some(END_OF_DAY)
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.legacy {
+            write!(f, "{} {}_{} (", format_time(self.start), self.project, self.task)?;
+        } else {
+            write!(f, "{} {} {} (", format_time(self.start), quote_name(&self.project), quote_name(&self.task))?;
+        }
+        if let Some(end) = self.end {
+            write!(f, "{}) {}", escape_line_breaks(self.description.as_deref().unwrap_or("")), format_time(end))?;
+        }
+        Ok(())
+    }
+}
+
+// All the entries of one day file, in the same order as in the file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayLog {
+    pub entries: Vec<Entry>,
+}
+
+impl DayLog {
+    pub fn open_entry(&self) -> Option<&Entry> {
+        self.entries.last().filter(|e| e.is_open())
+    }
+
+    pub fn open_entry_mut(&mut self) -> Option<&mut Entry> {
+        self.entries.last_mut().filter(|e| e.is_open())
+    }
+
+    pub fn last_closed_entry(&self) -> Option<&Entry> {
+        self.entries.iter().rev().find(|e| !e.is_open())
+    }
+
+    // A new entry can't start before the previous one ended
+    pub fn check_start(&self, start: NaiveTime) -> Result<(), StorageError> {
+        if let Some(open) = self.open_entry() {
+            return Err(StorageError::AlreadyRunning { project: open.project.clone(), task: open.task.clone(), start: open.start });
+        }
+        match self.last_closed_entry().and_then(|last| last.end.map(|end| (last, end))) {
+            Some((last, end)) if start < end => Err(StorageError::InvalidTime(format!(
+                "{} overlaps {} / {} which ended at {}",
+                start.format(TIME_FORMAT), last.project, last.task, end.format(TIME_FORMAT)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // The running entry can't end before it started
+    pub fn check_end(&self, end: NaiveTime) -> Result<(), StorageError> {
+        match self.open_entry() {
+            Some(open) if end < open.start => Err(StorageError::InvalidTime(format!(
+                "{} is before {} / {} started at {}",
+                end.format(TIME_FORMAT), open.project, open.task, open.start.format(TIME_FORMAT)
+            ))),
+            Some(_) => Ok(()),
+            None => Err(StorageError::NothingRunning),
+        }
+    }
+}
+
+impl fmt::Display for DayLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            write!(f, "{}", entry)?;
+            // The open entry stays without '\n' so the closing half can be appended
+            if !entry.is_open() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // 1-based, like an editor
+    pub line: usize,
+    pub content: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} ({:?})", self.line, self.reason, self.content)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse_day_log(content: &str) -> Result<DayLog, ParseError> {
+    let mut day_log = DayLog::default();
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (index, raw_line) in lines.iter().enumerate() {
+        let line = raw_line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Only the last line can be open and only when the file doesn't end with '\n'
+        let may_be_open = index == lines.len() - 1 && !content.ends_with('\n');
+        let entry = parse_entry(line, may_be_open).map_err(|reason| ParseError {
+            line: index + 1,
+            content: line.to_string(),
+            reason,
+        })?;
+        day_log.entries.push(entry);
+    }
+
+    Ok(day_log)
+}
+
+fn parse_entry(line: &str, may_be_open: bool) -> Result<Entry, String> {
+    let (start, rest) = line.split_once(' ')
+        .ok_or_else(|| "missing project and task".to_string())?;
+    let start = parse_time(start)?;
+
+    let (mut entry, rest) = if rest.starts_with('"') {
+        let (project, rest) = unquote_name(rest)?;
+        let rest = rest.strip_prefix(' ')
+            .ok_or_else(|| "missing task after project".to_string())?;
+        let (task, rest) = unquote_name(rest)?;
+        let rest = rest.strip_prefix(" (")
+            .ok_or_else(|| "missing '(' after project and task".to_string())?;
+        (Entry::new(start, &project, &task), rest)
+    } else {
+        let (name, rest) = rest.split_once(" (")
+            .ok_or_else(|| "missing '(' after project and task".to_string())?;
+        let (project, task) = name.split_once('_')
+            .ok_or_else(|| format!("'{}' is not in project_task form", name))?;
+        if project.is_empty() || task.is_empty() {
+            return Err(format!("'{}' is not in project_task form", name));
+        }
+        let mut entry = Entry::new(start, project, task);
+        entry.legacy = true;
+        (entry, rest)
+    };
+
+    // The description can contain ')' so the last ") " is the one that closes it
+    if let Some((description, end)) = rest.rsplit_once(") ") {
+        entry.close(parse_time(end)?, &unescape_line_breaks(description));
+        return Ok(entry);
+    }
+    if may_be_open && rest.is_empty() {
+        return Ok(entry);
+    }
+    Err("entry is not closed".to_string())
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, TIME_FORMAT)
+        .or_else(|_| NaiveTime::parse_from_str(s, TIME_FORMAT_SECONDS))
+        .map_err(|_| format!("'{}' is not a valid HH:MM time", s))
+}
+
+// Seconds are only written when there are any
+pub fn format_time(time: NaiveTime) -> String {
+    if time.second() == 0 {
+        time.format(TIME_FORMAT).to_string()
+    } else {
+        time.format(TIME_FORMAT_SECONDS).to_string()
+    }
+}
+
+// Names go between double quotes, escaping '"', '\\' and line breaks so any UTF-8 name survives
+pub fn quote_name(name: &str) -> String {
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// A line break in a description or a task name would split its line in two. A backslash
+// is only escaped when an escape follows it, so old lines like "C:\2024" read the same
+pub fn escape_line_breaks(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' if matches!(chars.peek(), Some('n' | 'r' | '\\' | '\n' | '\r')) => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn unescape_line_breaks(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('n')) => { chars.next(); unescaped.push('\n'); },
+            ('\\', Some('r')) => { chars.next(); unescaped.push('\r'); },
+            ('\\', Some('\\')) => { chars.next(); unescaped.push('\\'); },
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Reads a quoted name from the start of `s` and returns it with the rest of the line
+fn unquote_name(s: &str) -> Result<(String, &str), String> {
+    let mut name = String::new();
+    let mut chars = s.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((name, &s[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => name.push('\n'),
+                Some((_, 'r')) => name.push('\r'),
+                Some((_, escaped)) => name.push(escaped),
+                None => break,
+            },
+            _ => name.push(c),
+        }
+    }
+    Err(format!("unterminated quoted name in {}", s))
+}
+
+// How the first versions wrote "project_task", kept to recognise old lines
+pub fn legacy_name(project: &str, task: &str) -> String {
+    format!("{}_{}", project.replace(' ', "-"), task.replace(' ', "-"))
+}
@@ -0,0 +1,70 @@
+use std::{fmt, io};
+use std::path::{Path, PathBuf};
+use chrono::NaiveTime;
+use crate::data_managing::day_log::{self, ParseError};
+
+// What can go wrong while reading or writing projects and days. Core never prints
+// them, the caller decides how to show each one
+#[derive(Debug)]
+pub enum StorageError {
+    // What is missing, like "Project 'Diseño'"
+    NotFound(String),
+    AlreadyExists(String),
+    MalformedLine {
+        path: PathBuf,
+        error: ParseError,
+    },
+    PermissionDenied {
+        path: PathBuf,
+    },
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    // The timer operations that can't be done now
+    AlreadyRunning {
+        project: String,
+        task: String,
+        start: NaiveTime,
+    },
+    NothingRunning,
+    // A start or end that overlaps another entry or comes before the start, with the reason
+    InvalidTime(String),
+}
+
+impl StorageError {
+    // Permission problems get their own variant, the rest keep the IO error
+    pub fn io(path: &Path, source: io::Error) -> StorageError {
+        match source.kind() {
+            io::ErrorKind::PermissionDenied => StorageError::PermissionDenied { path: path.to_path_buf() },
+            _ => StorageError::Io { path: path.to_path_buf(), source },
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound(what) => write!(f, "{} not found", what),
+            StorageError::AlreadyExists(what) => write!(f, "{} already exists", what),
+            StorageError::MalformedLine { path, error } => write!(f, "Malformed day file {}: {}", path.display(), error),
+            StorageError::PermissionDenied { path } => write!(f, "Permission denied: {}", path.display()),
+            StorageError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            StorageError::AlreadyRunning { project, task, start } => {
+                write!(f, "{} / {} is already running since {}", project, task, start.format(day_log::TIME_FORMAT))
+            },
+            StorageError::NothingRunning => write!(f, "There is no running entry"),
+            StorageError::InvalidTime(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::MalformedLine { error, .. } => Some(error),
+            StorageError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, bail};
+use chrono::{NaiveDate, TimeDelta};
+use chrono::format::{self, Item, Parsed, StrftimeItems};
+
+// Where the layout of a data folder is kept, inside the data folder
+pub const LAYOUT_FILE: &str = ".layout";
+// Week folders with one file per day, how the data folder has always looked
+pub const DEFAULT_LAYOUT: &str = "Weeks/{isoyear} W{isoweek}/{date:%d-%m-%Y}.txt";
+
+// Where each day is stored inside the data folder, written as a path template:
+//   {year} {month} {day}       -> 2026, 01, 05
+//   {isoyear} {isoweek}        -> ISO week-year and week, 2026, 02
+//   {date:FORMAT}              -> any chrono format, like {date:%Y-%m-%d}
+// When several days share a path (monthly files) the file keeps one section per day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    template: String,
+    // The template turned into a chrono format
+    format: String,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::parse(DEFAULT_LAYOUT).expect("The default layout is valid")
+    }
+}
+
+impl Layout {
+    pub fn parse(template: &str) -> Result<Layout> {
+        let template = template.trim();
+        let mut format = String::new();
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '{' => {
+                    let (placeholder, after) = rest[1..].split_once('}')
+                        .with_context(|| format!("Unclosed '{{' in layout '{}'", template))?;
+                    match placeholder {
+                        "year" => format.push_str("%Y"),
+                        "month" => format.push_str("%m"),
+                        "day" => format.push_str("%d"),
+                        "isoyear" => format.push_str("%G"),
+                        "isoweek" => format.push_str("%V"),
+                        _ => match placeholder.strip_prefix("date:") {
+                            Some(date_format) if !date_format.is_empty() => format.push_str(date_format),
+                            _ => bail!("Unknown placeholder {{{}}} in layout '{}'", placeholder, template),
+                        },
+                    }
+                    rest = after;
+                },
+                '%' => {
+                    format.push_str("%%");
+                    rest = &rest[1..];
+                },
+                _ => {
+                    format.push(c);
+                    rest = &rest[c.len_utf8()..];
+                },
+            }
+        }
+
+        if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+            bail!("Invalid date format in layout '{}'", template);
+        }
+        let layout = Layout { template: template.to_string(), format };
+        let sample = layout.relative_path(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default());
+        if template.is_empty() || sample.is_absolute()
+            || sample.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+            bail!("The layout '{}' must be a path inside the data folder", template);
+        }
+        Ok(layout)
+    }
+
+    // The layout written in the data folder, or the default one
+    pub fn load(data_path: &Path) -> Result<Layout> {
+        let layout_file = data_path.join(LAYOUT_FILE);
+        if !layout_file.exists() {
+            return Ok(Layout::default());
+        }
+        let template = fs::read_to_string(&layout_file)
+            .with_context(|| format!("Could not read layout file: {}", layout_file.display()))?;
+        Layout::parse(&template)
+            .with_context(|| format!("Malformed layout file: {}", layout_file.display()))
+    }
+
+    // Written to a temporary file first so readers never see half a layout
+    pub fn save(&self, data_path: &Path) -> Result<()> {
+        let layout_file = data_path.join(LAYOUT_FILE);
+        let temporary = data_path.join(format!("{}.tmp", LAYOUT_FILE));
+        fs::create_dir_all(data_path)
+            .with_context(|| format!("Could not create folder: {}", data_path.display()))?;
+        fs::write(&temporary, format!("{}\n", self.template))
+            .with_context(|| format!("Could not write layout file: {}", temporary.display()))?;
+        fs::rename(&temporary, &layout_file)
+            .with_context(|| format!("Could not write layout file: {}", layout_file.display()))
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.template == DEFAULT_LAYOUT
+    }
+
+    pub fn relative_path(&self, date: NaiveDate) -> PathBuf {
+        PathBuf::from(date.format(&self.format).to_string())
+    }
+
+    pub fn path(&self, data_path: &Path, date: NaiveDate) -> PathBuf {
+        data_path.join(self.relative_path(date))
+    }
+
+    // True when a file keeps several days, like monthly or weekly files
+    pub fn groups_days(&self) -> bool {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default();
+        self.relative_path(monday) == self.relative_path(monday + TimeDelta::days(1))
+    }
+
+    // Folders before the first placeholder, every day file is inside them
+    pub fn root(&self) -> PathBuf {
+        let fixed = self.template.split('{').next().unwrap_or("");
+        match fixed.rsplit_once('/') {
+            Some((folders, _)) => PathBuf::from(folders),
+            None => PathBuf::new(),
+        }
+    }
+
+    // Whether a path relative to the data folder can be a file of this layout
+    pub fn matches(&self, relative: &Path) -> bool {
+        let Some(relative) = relative.to_str() else {
+            return false;
+        };
+        let mut parsed = Parsed::new();
+        format::parse(&mut parsed, &relative.replace('\\', "/"), StrftimeItems::new(&self.format)).is_ok()
+    }
+
+    // Date of a one-day file. Only the file name is used when the folders don't agree
+    // with it, so a file left in the wrong folder is still found
+    pub fn date_of(&self, relative: &Path) -> Option<NaiveDate> {
+        let relative = relative.to_str()?.replace('\\', "/");
+        let mut parsed = Parsed::new();
+        if format::parse(&mut parsed, &relative, StrftimeItems::new(&self.format)).is_ok()
+            && let Ok(date) = parsed.to_naive_date() {
+            return Some(date);
+        }
+        let file_format = self.format.rsplit('/').next()?;
+        let file_name = relative.rsplit('/').next()?;
+        NaiveDate::parse_from_str(file_name, file_format).ok()
+    }
+}
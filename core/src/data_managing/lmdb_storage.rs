@@ -0,0 +1,404 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use anyhow::{Result, Context};
+use chrono::{NaiveDate, NaiveTime};
+use heed::byteorder::BE;
+use heed::types::{SerdeJson, Str, Unit, U64};
+use heed::{Database, Env, EnvOpenOptions, RwTxn};
+use serde::{Deserialize, Serialize};
+use crate::data_managing::day_log::{self, DayLog, Entry};
+use crate::clock::{Clock, SystemClock};
+use crate::data_managing::Storage;
+use crate::data_managing::error::StorageError;
+use crate::data_managing::text_storage;
+use crate::data_managing::layout::Layout;
+
+// Folder inside the data path where the LMDB environment lives
+pub const DB_PATH: &str = "db";
+
+// Keys of the indexes are built joining the parts with this separator
+const SEP: char = '\0';
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredEntry {
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub end: Option<NaiveTime>,
+    pub project: String,
+    pub task: String,
+    pub description: Option<String>,
+}
+
+impl StoredEntry {
+    pub fn from_entry(date: NaiveDate, entry: &Entry) -> StoredEntry {
+        StoredEntry {
+            date,
+            start: entry.start,
+            end: entry.end,
+            project: entry.project.clone(),
+            task: entry.task.clone(),
+            description: entry.description.clone(),
+        }
+    }
+
+    pub fn to_entry(&self) -> Entry {
+        let mut entry = Entry::new(self.start, &self.project, &self.task);
+        entry.end = self.end;
+        entry.description = self.description.clone();
+        entry
+    }
+}
+
+pub struct LmdbStorage {
+    env: Env,
+    // project -> ()
+    projects: Database<Str, Unit>,
+    // project\0task -> ()
+    tasks: Database<Str, Unit>,
+    // id -> entry
+    entries: Database<U64<BE>, SerdeJson<StoredEntry>>,
+    // YYYY-MM-DD\0id, project\0id and project\0task\0id -> ()
+    by_day: Database<Str, Unit>,
+    by_project: Database<Str, Unit>,
+    by_task: Database<Str, Unit>,
+    clock: Rc<dyn Clock>,
+}
+
+// Ids are zero padded so the index keys keep the insertion order
+fn index_key(parts: &[&str], id: u64) -> String {
+    let mut key = String::new();
+    for part in parts {
+        key.push_str(part);
+        key.push(SEP);
+    }
+    key.push_str(&format!("{:020}", id));
+    key
+}
+
+fn index_prefix(parts: &[&str]) -> String {
+    let mut prefix = String::new();
+    for part in parts {
+        prefix.push_str(part);
+        prefix.push(SEP);
+    }
+    prefix
+}
+
+impl LmdbStorage {
+    pub fn open(data_path: &Path) -> Result<LmdbStorage> {
+        let db_path = data_path.join(DB_PATH);
+        fs::create_dir_all(&db_path)
+            .with_context(|| format!("Could not create database folder: {}", db_path.display()))?;
+
+        // Safety: the environment is only opened once per process
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(1024 * 1024 * 1024)
+                .max_dbs(6)
+                .open(&db_path)
+        }.with_context(|| format!("Could not open database at: {}", db_path.display()))?;
+
+        let mut wtxn = env.write_txn()?;
+        let storage = LmdbStorage {
+            projects: env.create_database(&mut wtxn, Some("projects"))?,
+            tasks: env.create_database(&mut wtxn, Some("tasks"))?,
+            entries: env.create_database(&mut wtxn, Some("entries"))?,
+            by_day: env.create_database(&mut wtxn, Some("entries_by_day"))?,
+            by_project: env.create_database(&mut wtxn, Some("entries_by_project"))?,
+            by_task: env.create_database(&mut wtxn, Some("entries_by_task"))?,
+            env: env.clone(),
+            clock: Rc::new(SystemClock),
+        };
+        wtxn.commit()?;
+        Ok(storage)
+    }
+
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> LmdbStorage {
+        self.clock = clock;
+        self
+    }
+
+    pub fn add_project(&self, project: &str) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.projects.put(&mut wtxn, project, &())?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    pub fn add_task(&self, project: &str, task: &str) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.projects.put(&mut wtxn, project, &())?;
+        self.tasks.put(&mut wtxn, &index_prefix(&[project, task]), &())?;
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    // Stores the entry with its indexes and returns its id
+    pub fn add_entry(&self, date: NaiveDate, entry: &Entry) -> Result<u64> {
+        let mut wtxn = self.env.write_txn()?;
+        let id = self.put_entry(&mut wtxn, date, entry)?;
+        wtxn.commit()?;
+        Ok(id)
+    }
+
+    fn put_entry(&self, wtxn: &mut RwTxn, date: NaiveDate, entry: &Entry) -> Result<u64> {
+        let id = self.entries.last(wtxn)?.map(|(id, _)| id + 1).unwrap_or(0);
+        let day = date.format("%Y-%m-%d").to_string();
+
+        self.entries.put(wtxn, &id, &StoredEntry::from_entry(date, entry))?;
+        self.by_day.put(wtxn, &index_key(&[&day], id), &())?;
+        self.by_project.put(wtxn, &index_key(&[&entry.project], id), &())?;
+        self.by_task.put(wtxn, &index_key(&[&entry.project, &entry.task], id), &())?;
+        self.projects.put(wtxn, &entry.project, &())?;
+        self.tasks.put(wtxn, &index_prefix(&[&entry.project, &entry.task]), &())?;
+        Ok(id)
+    }
+
+    // Removes every entry of the day with its indexes and stores the new ones
+    pub fn replace_day(&self, date: NaiveDate, entries: &[Entry]) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        let day = date.format("%Y-%m-%d").to_string();
+        let prefix = index_prefix(&[&day]);
+
+        let mut ids = Vec::new();
+        for item in self.by_day.prefix_iter(&wtxn, &prefix)? {
+            let key = item?.0;
+            ids.push(key[prefix.len()..].parse::<u64>()
+                .with_context(|| format!("Corrupted index key: {:?}", key))?);
+        }
+        for id in ids {
+            if let Some(old) = self.entries.get(&wtxn, &id)? {
+                self.by_project.delete(&mut wtxn, &index_key(&[&old.project], id))?;
+                self.by_task.delete(&mut wtxn, &index_key(&[&old.project, &old.task], id))?;
+            }
+            self.by_day.delete(&mut wtxn, &index_key(&[&day], id))?;
+            self.entries.delete(&mut wtxn, &id)?;
+        }
+
+        for entry in entries {
+            self.put_entry(&mut wtxn, date, entry)?;
+        }
+        wtxn.commit()?;
+        Ok(())
+    }
+
+    pub fn days(&self) -> Result<Vec<NaiveDate>> {
+        let rtxn = self.env.read_txn()?;
+        let mut days: Vec<NaiveDate> = Vec::new();
+        for item in self.by_day.iter(&rtxn)? {
+            let key = item?.0;
+            let day = key.split(SEP).next().unwrap_or_default();
+            let date = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .with_context(|| format!("Corrupted index key: {:?}", key))?;
+            if days.last() != Some(&date) {
+                days.push(date);
+            }
+        }
+        Ok(days)
+    }
+
+    pub fn get_projects(&self) -> Result<Vec<String>> {
+        let rtxn = self.env.read_txn()?;
+        let mut projects = Vec::new();
+        for item in self.projects.iter(&rtxn)? {
+            projects.push(item?.0.to_string());
+        }
+        Ok(projects)
+    }
+
+    pub fn get_tasks(&self, project: &str) -> Result<Vec<String>> {
+        let rtxn = self.env.read_txn()?;
+        let prefix = index_prefix(&[project]);
+        let mut tasks = Vec::new();
+        for item in self.tasks.prefix_iter(&rtxn, &prefix)? {
+            let key = item?.0;
+            tasks.push(key[prefix.len()..].trim_end_matches(SEP).to_string());
+        }
+        Ok(tasks)
+    }
+
+    pub fn entries_on_day(&self, date: NaiveDate) -> Result<Vec<StoredEntry>> {
+        self.entries_from_index(&self.by_day, &[&date.format("%Y-%m-%d").to_string()])
+    }
+
+    pub fn entries_for_project(&self, project: &str) -> Result<Vec<StoredEntry>> {
+        self.entries_from_index(&self.by_project, &[project])
+    }
+
+    pub fn entries_for_task(&self, project: &str, task: &str) -> Result<Vec<StoredEntry>> {
+        self.entries_from_index(&self.by_task, &[project, task])
+    }
+
+    fn entries_from_index(&self, index: &Database<Str, Unit>, parts: &[&str]) -> Result<Vec<StoredEntry>> {
+        let rtxn = self.env.read_txn()?;
+        let prefix = index_prefix(parts);
+        let mut entries = Vec::new();
+        for item in index.prefix_iter(&rtxn, &prefix)? {
+            let key = item?.0;
+            let id: u64 = key[prefix.len()..].parse()
+                .with_context(|| format!("Corrupted index key: {:?}", key))?;
+            if let Some(entry) = self.entries.get(&rtxn, &id)? {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn counts(&self) -> Result<StorageCounts> {
+        let rtxn = self.env.read_txn()?;
+        Ok(StorageCounts {
+            projects: self.projects.len(&rtxn)? as usize,
+            tasks: self.tasks.len(&rtxn)? as usize,
+            entries: self.entries.len(&rtxn)? as usize,
+        })
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        let mut wtxn = self.env.write_txn()?;
+        self.projects.clear(&mut wtxn)?;
+        self.tasks.clear(&mut wtxn)?;
+        self.entries.clear(&mut wtxn)?;
+        self.by_day.clear(&mut wtxn)?;
+        self.by_project.clear(&mut wtxn)?;
+        self.by_task.clear(&mut wtxn)?;
+        wtxn.commit()?;
+        Ok(())
+    }
+}
+
+impl Storage for LmdbStorage {
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn get_projects(&self) -> Result<Vec<String>> {
+        LmdbStorage::get_projects(self)
+    }
+
+    fn get_tasks(&self, project: &str) -> Result<Vec<String>> {
+        LmdbStorage::get_tasks(self, project)
+    }
+
+    fn create_project(&self, project: &str) -> Result<()> {
+        if LmdbStorage::get_projects(self)?.iter().any(|p| p == project) {
+            return Err(StorageError::AlreadyExists(format!("Project '{}'", project)).into());
+        }
+        self.add_project(project)
+    }
+
+    fn create_task(&self, project: &str, task: &str) -> Result<()> {
+        self.add_task(project, task)
+    }
+
+    fn read_day(&self, date: NaiveDate) -> Result<DayLog> {
+        Ok(DayLog {
+            entries: self.entries_on_day(date)?.iter().map(StoredEntry::to_entry).collect(),
+        })
+    }
+
+    fn write_day(&self, date: NaiveDate, day: &DayLog) -> Result<()> {
+        self.replace_day(date, &day.entries)
+    }
+
+    fn days(&self) -> Result<Vec<NaiveDate>> {
+        LmdbStorage::days(self)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StorageCounts {
+    pub projects: usize,
+    pub tasks: usize,
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+pub struct ImportReport {
+    // What was read from the text files
+    pub expected: StorageCounts,
+    // What the database holds after the import
+    pub stored: StorageCounts,
+}
+
+impl ImportReport {
+    pub fn is_complete(&self) -> bool {
+        self.expected == self.stored
+    }
+}
+
+// Replaces the content of the database with every project, task and day file
+// of the text storage and counts both sides so the caller can check them
+pub fn import_text_storage(data_path: &Path, storage: &LmdbStorage) -> Result<ImportReport> {
+    let text = text_storage::TextStorage::open(data_path)?;
+    let expected = count_text_storage(data_path, text.layout())?;
+
+    // Everything goes in one transaction so a failed import leaves the old database
+    let mut wtxn = storage.env.write_txn()?;
+    storage.projects.clear(&mut wtxn)?;
+    storage.tasks.clear(&mut wtxn)?;
+    storage.entries.clear(&mut wtxn)?;
+    storage.by_day.clear(&mut wtxn)?;
+    storage.by_project.clear(&mut wtxn)?;
+    storage.by_task.clear(&mut wtxn)?;
+
+    for project in text_storage::get_projects(data_path)? {
+        storage.projects.put(&mut wtxn, &project, &())?;
+        for task in text_storage::get_tasks_from_project(data_path, &project)? {
+            storage.tasks.put(&mut wtxn, &index_prefix(&[&project, &task]), &())?;
+        }
+    }
+    for date in text.days()? {
+        for entry in &text.read_day(date)?.entries {
+            storage.put_entry(&mut wtxn, date, entry)?;
+        }
+    }
+    wtxn.commit()?;
+
+    Ok(ImportReport { expected, stored: storage.counts()? })
+}
+
+// Counts the text files on their own, without the storage that does the import:
+// entries are the non blank lines of the day files, not the parsed ones
+fn count_text_storage(data_path: &Path, layout: &Layout) -> Result<StorageCounts> {
+    let mut projects = BTreeSet::new();
+    let mut tasks = BTreeSet::new();
+    let mut entries = 0;
+
+    let projects_path = data_path.join(text_storage::PROYECTS_PATH);
+    if projects_path.exists() {
+        for file in fs::read_dir(&projects_path).with_context(|| format!("Could not read {}", projects_path.display()))? {
+            let path = file?.path();
+            let Some(project) = path.file_name().and_then(|name| name.to_str()).map(text_storage::project_from_file_name) else {
+                continue;
+            };
+            let content = fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))?;
+            for task in content.lines() {
+                tasks.insert((project.clone(), day_log::unescape_line_breaks(task)));
+            }
+            projects.insert(project);
+        }
+    }
+
+    for day_file in text_storage::get_day_files(data_path, layout)? {
+        let relative = day_file.strip_prefix(data_path).unwrap_or(&day_file);
+        let days = if layout.groups_days() {
+            text_storage::read_grouped_day_logs(&day_file)?.into_values().collect()
+        } else if layout.date_of(relative).is_some() {
+            vec![text_storage::read_day_log(&day_file)?]
+        } else {
+            continue;
+        };
+        let content = fs::read_to_string(&day_file).with_context(|| format!("Could not read {}", day_file.display()))?;
+        let is_header = |line: &str| layout.groups_days() && line.starts_with("# ");
+        entries += content.lines().filter(|line| !line.trim().is_empty() && !is_header(line)).count();
+        // Entries can name projects and tasks that no longer have a file
+        for entry in days.iter().flat_map(|day| &day.entries) {
+            projects.insert(entry.project.clone());
+            tasks.insert((entry.project.clone(), entry.task.clone()));
+        }
+    }
+
+    Ok(StorageCounts { projects: projects.len(), tasks: tasks.len(), entries })
+}
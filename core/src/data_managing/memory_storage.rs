@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use anyhow::Result;
+use chrono::NaiveDate;
+use crate::data_managing::day_log::DayLog;
+use crate::clock::{Clock, SystemClock};
+use crate::data_managing::Storage;
+use crate::data_managing::error::StorageError;
+
+// Storage that lives only while the value does, meant for tests
+#[derive(Debug)]
+pub struct MemoryStorage {
+    projects: RefCell<BTreeMap<String, Vec<String>>>,
+    days: RefCell<BTreeMap<NaiveDate, DayLog>>,
+    clock: Rc<dyn Clock>,
+}
+
+impl Default for MemoryStorage {
+    fn default() -> MemoryStorage {
+        MemoryStorage {
+            projects: RefCell::default(),
+            days: RefCell::default(),
+            clock: Rc::new(SystemClock),
+        }
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> MemoryStorage {
+        self.clock = clock;
+        self
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    fn get_projects(&self) -> Result<Vec<String>> {
+        Ok(self.projects.borrow().keys().cloned().collect())
+    }
+
+    fn get_tasks(&self, project: &str) -> Result<Vec<String>> {
+        match self.projects.borrow().get(project) {
+            Some(tasks) => Ok(tasks.clone()),
+            None => Err(StorageError::NotFound(format!("Project '{}'", project)).into()),
+        }
+    }
+
+    fn create_project(&self, project: &str) -> Result<()> {
+        let mut projects = self.projects.borrow_mut();
+        if projects.contains_key(project) {
+            return Err(StorageError::AlreadyExists(format!("Project '{}'", project)).into());
+        }
+        projects.insert(project.to_string(), Vec::new());
+        Ok(())
+    }
+
+    fn create_task(&self, project: &str, task: &str) -> Result<()> {
+        match self.projects.borrow_mut().get_mut(project) {
+            Some(tasks) => tasks.push(task.to_string()),
+            None => return Err(StorageError::NotFound(format!("Project '{}'", project)).into()),
+        }
+        Ok(())
+    }
+
+    fn read_day(&self, date: NaiveDate) -> Result<DayLog> {
+        Ok(self.days.borrow().get(&date).cloned().unwrap_or_default())
+    }
+
+    fn write_day(&self, date: NaiveDate, day: &DayLog) -> Result<()> {
+        self.days.borrow_mut().insert(date, day.clone());
+        Ok(())
+    }
+
+    fn days(&self) -> Result<Vec<NaiveDate>> {
+        Ok(self.days.borrow().iter()
+            .filter(|(_, day)| !day.entries.is_empty())
+            .map(|(date, _)| *date)
+            .collect())
+    }
+}
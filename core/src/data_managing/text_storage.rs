@@ -1,157 +1,599 @@
-use std::{fs, path::PathBuf};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::io::{ErrorKind, Write};
 use std::fs::{OpenOptions};
-use anyhow::{Result, Context};
+use anyhow::{Result, Context, bail};
 use chrono::prelude::*;
+use crate::data_managing::day_log::{self, DayLog, Entry, ParseError};
+use crate::data_managing::error::StorageError;
+use crate::data_managing::layout::Layout;
+use crate::data_managing::lmdb_storage::DB_PATH;
+use crate::data_managing::Storage;
+use crate::clock::{Clock, SystemClock};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 
 pub const WEEKS_PATH: &str = "Weeks";
 pub const PROYECTS_PATH: &str = "Projects";
 
-//TODO: devolver vacío y comunicar y si algo no funcionó (para todas las funciones?)
-
-pub fn init(data_path: &PathBuf) -> PathBuf {
-    // Check if todays file exists, if not create it
-    let filename_path = get_todays_filename(data_path);
+// Creates today's file when it doesn't exist yet
+pub fn init(data_path: &Path, layout: &Layout, today: NaiveDate) -> Result<PathBuf, StorageError> {
+    let filename_path = layout.path(data_path, today);
+    create_parent_folder(&filename_path)?;
     if !filename_path.exists() {
-        println!("Todays file does not exist, creating it...");
-        if let Err(e) = fs::write(&filename_path, "") {
-            println!("Error creating file: {}", e);
+        fs::write(&filename_path, "").map_err(|e| StorageError::io(&filename_path, e))?;
+    }
+    Ok(filename_path)
+}
+
+fn create_parent_folder(file: &Path) -> Result<(), StorageError> {
+    match file.parent() {
+        Some(folder_path) => fs::create_dir_all(folder_path).map_err(|e| StorageError::io(folder_path, e)),
+        None => Ok(()),
+    }
+}
+
+fn construct_project_path(data_path: &Path, project_name: &str) -> PathBuf {
+    data_path.join(PROYECTS_PATH).join(project_file_name(project_name))
+}
+
+// Project files are named after the project. '%', '/', '\\' and control characters
+// can't go in a file name and are written as "%XX", any other name stays readable
+pub fn project_file_name(project_name: &str) -> String {
+    let mut file_name = String::with_capacity(project_name.len() + 4);
+    for c in project_name.chars() {
+        if matches!(c, '%' | '/' | '\\') || c.is_control() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
         } else {
-            println!("Todays file created sucessfully");
+            file_name.push(c);
         }
     }
-    return filename_path;
+    file_name.push_str(".txt");
+    file_name
 }
 
-fn construct_project_path(data_path: &PathBuf, project_name: &str) -> PathBuf {
-    if project_name.ends_with(".txt") {
-        return data_path.join(PROYECTS_PATH).join(project_name);
-    } else {
-        return PathBuf::from(format!("{}/{}.txt", data_path.join(PROYECTS_PATH).display(), project_name))
+// A '%' that is not followed by two hex digits was written by hand and is kept
+pub fn project_from_file_name(file_name: &str) -> String {
+    let stem = file_name.strip_suffix(".txt").unwrap_or(file_name).as_bytes();
+    let mut bytes = Vec::with_capacity(stem.len());
+    let mut i = 0;
+    while i < stem.len() {
+        let escaped = stem.get(i + 1..i + 3)
+            .filter(|_| stem[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            },
+            None => {
+                bytes.push(stem[i]);
+                i += 1;
+            },
+        }
     }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn project_not_found(project_name: &str) -> StorageError {
+    StorageError::NotFound(format!("Project '{}'", project_name))
 }
 
 // It also creates the needed folders if they doesnt exist
-pub fn get_todays_filename(data_path: &PathBuf) -> PathBuf {
-    let now = Local::now();
-    let week = now.iso_week().week();
-    let year = now.year();
-    let folder_path = format!("{}/{} W{:02}", data_path.join(WEEKS_PATH).display(), year, week);
-    fs::create_dir_all(&folder_path).expect("Failed to create directory");
-    let filename = format!("{}/{}.txt", folder_path, now.format("%d-%m-%Y"));
-    PathBuf::from(filename)
+pub fn get_todays_filename(data_path: &Path, clock: &dyn Clock) -> Result<PathBuf, StorageError> {
+    let filename = get_day_filename(data_path, clock.today());
+    create_parent_folder(&filename)?;
+    Ok(filename)
+}
+
+// File of the day in the default layout. The folder uses the ISO week-year,
+// 29-12-2025 belongs to "2026 W01"
+pub fn get_day_filename(data_path: &Path, date: NaiveDate) -> PathBuf {
+    Layout::default().path(data_path, date)
+}
+
+// Where older versions wrote the file, with the calendar year and the ISO week
+fn get_misfiled_day_filename(data_path: &Path, date: NaiveDate) -> PathBuf {
+    let folder_path = format!("{}/{} W{:02}", data_path.join(WEEKS_PATH).display(), date.year(), date.iso_week().week());
+    PathBuf::from(format!("{}/{}.txt", folder_path, date.format("%d-%m-%Y")))
 }
 
+// Day file to read, falling back to the old location until migrate-weeks is run
+fn find_day_file(data_path: &Path, layout: &Layout, date: NaiveDate) -> PathBuf {
+    let filename = layout.path(data_path, date);
+    let misfiled = get_misfiled_day_filename(data_path, date);
+    if layout.is_default() && !filename.exists() && misfiled.exists() {
+        misfiled
+    } else {
+        filename
+    }
+}
+
+// Names of the projects folder files, which is created when it is missing
 // TODO: Usar un search para no traer todos proyectos
-pub fn get_projects(data_path: &PathBuf) -> Vec<String> {
-    match fs::read_dir(data_path.join(PROYECTS_PATH)) {
-        // Get all entries of dir
-        Ok(entries) => {
-            entries
-                .filter_map(|entry| {
-                    entry.ok().and_then(|e| e.file_name().into_string().ok())
-                })
-                .collect()
+pub fn get_projects(data_path: &Path) -> Result<Vec<String>, StorageError> {
+    let projects_path = data_path.join(PROYECTS_PATH);
+    match fs::read_dir(&projects_path) {
+        Ok(entries) => Ok(entries
+            .filter_map(|entry| entry.ok().and_then(|e| e.file_name().into_string().ok()))
+            .map(|file_name| project_from_file_name(&file_name))
+            .collect()),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            //TODO: Ask to create before creating it
+            fs::create_dir_all(&projects_path).map_err(|e| StorageError::io(&projects_path, e))?;
+            Ok(Vec::new())
         },
-        // If an errors pop, show it
-        Err(e) => {
-            match e.kind() {
-                ErrorKind::NotFound => {
-                    //TODO: Ask to create before creating it
-                    println!("Proyect folder not found at '{}', creating it ...", data_path.join(PROYECTS_PATH).display());
-                    if let Err(create_error) = fs::create_dir_all(data_path.join(PROYECTS_PATH)) { 
-                        eprintln!("Failed to create the proyect folder, Reason: {}", create_error);
-                    } else {
-                        println!("Proyect folder created successfully.");
-                    }
-                    Vec::new()
-                },
-                // Handle permission denied or other IO errors
-                _ => {
-                    eprintln!("Generic error reading directory: {}", e);
-                    Vec::new()
-                }
-            }
-        }
+        Err(e) => Err(StorageError::io(&projects_path, e)),
     }
 }
 
 // TODO: Usar un search para no traer todos proyectos
-pub fn get_tasks_from_project(data_path: &PathBuf, project_name: &str) -> Result<Vec<String>> {
-    let project_path = format!("{}/{}", data_path.join(PROYECTS_PATH).display(), project_name);
-
-    // El operador '?' reemplaza todo el match. 
-    // Si falla, devuelve el error con el contexto que añadimos.
-    let content = fs::read_to_string(&project_path)
-        .with_context(|| format!("No se pudo leer el archivo del proyecto: {}", project_path))?
-    ;
-
-    let project_tasks: Vec<String> = content.lines()
-        .map(|line| line.to_string())
-        .collect()
-    ;
-
-    Ok(project_tasks)
+pub fn get_tasks_from_project(data_path: &Path, project_name: &str) -> Result<Vec<String>, StorageError> {
+    let project_path = construct_project_path(data_path, project_name);
+    let content = fs::read_to_string(&project_path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => project_not_found(project_name),
+        _ => StorageError::io(&project_path, e),
+    })?;
+    Ok(content.lines().map(day_log::unescape_line_breaks).collect())
 }
 
 // TODO: Dejar al usuario poder tener dos proyectos con el mismo nombre o dar la opción para que se pueda o no
-pub fn create_project(data_path: &PathBuf, project_name: &str) -> Result<String> {
-    // Create project storage folder if doesnt exist
-    if let Err(e) = fs::create_dir_all(data_path.join(PROYECTS_PATH)) {
-        eprintln!("Failed to create projects directory: {}", e);
-    }
-    // Create project file if doesnt exist
+pub fn create_project(data_path: &Path, project_name: &str) -> Result<String, StorageError> {
+    let projects_path = data_path.join(PROYECTS_PATH);
+    fs::create_dir_all(&projects_path).map_err(|e| StorageError::io(&projects_path, e))?;
     // Técnicamente no es posible guardar dos proyectos con el mismo nombre si se usa el nombre del proyecto como nombre de archivo
-    let resultado = OpenOptions::new()
-        .write(true)
-        .create_new(true) // Esta es la clave
-        .open(construct_project_path(data_path, project_name));
-    match resultado {
-        Ok(_) => {
-            println!("Archivo creado exitosamente.");
-            return Ok(project_name.to_string());
-        },
+    let project_path = construct_project_path(data_path, project_name);
+    match OpenOptions::new().write(true).create_new(true).open(&project_path) {
+        Ok(_) => Ok(project_name.to_string()),
         Err(e) if e.kind() == ErrorKind::AlreadyExists => {
-            return  Err(anyhow::anyhow!("El archivo ya existe, así que no hice nada."));
+            Err(StorageError::AlreadyExists(format!("Project '{}'", project_name)))
         },
-        Err(e) => panic!("Ocurrió un error inesperado: {}", e),
+        Err(e) => Err(StorageError::io(&project_path, e)),
     }
 }
 
-pub fn create_task(data_path: &PathBuf, project_name: &str, task_name: &str) {
-    let resultado = OpenOptions::new()
-        .write(true)
+pub fn create_task(data_path: &Path, project_name: &str, task_name: &str) -> Result<(), StorageError> {
+    let project_path = construct_project_path(data_path, project_name);
+    let mut file = OpenOptions::new().append(true).open(&project_path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => project_not_found(project_name),
+        _ => StorageError::io(&project_path, e),
+    })?;
+    // One task per line, a line break in the name can't split it
+    writeln!(file, "{}", day_log::escape_line_breaks(task_name)).map_err(|e| StorageError::io(&project_path, e))
+}
+
+pub fn start_timer_on_task(data_path: &Path, clock: &dyn Clock, project_name: &str, task_name: &str) -> Result<(), StorageError> {
+    let day_file = get_todays_filename(data_path, clock)?;
+    let mut file = OpenOptions::new()
         .append(true)
-        .open(construct_project_path(data_path, project_name));
-    match resultado {
-        Ok(mut file) => {
-            if let Err(e) = writeln!(file, "{}", task_name) {
-                eprintln!("Error writing to file: {}", e);
+        .create(true)
+        .open(&day_file)
+        .map_err(|e| StorageError::io(&day_file, e))?;
+    let entry = Entry::new(clock.current_minute(), project_name, task_name);
+    write!(file, "{}", entry).map_err(|e| StorageError::io(&day_file, e))
+}
+
+pub fn read_day_log(day_file: &Path) -> Result<DayLog, StorageError> {
+    if !day_file.exists() {
+        return Ok(DayLog::default());
+    }
+    let content = fs::read_to_string(day_file).map_err(|e| StorageError::io(day_file, e))?;
+    day_log::parse_day_log(&content)
+        .map_err(|error| StorageError::MalformedLine { path: day_file.to_path_buf(), error })
+}
+
+pub fn write_day_log(day_file: &Path, day: &DayLog) -> Result<(), StorageError> {
+    create_parent_folder(day_file)?;
+    fs::write(day_file, day.to_string()).map_err(|e| StorageError::io(day_file, e))
+}
+
+// Every file of the layout inside the data folder, sorted by path
+pub fn get_day_files(data_path: &Path, layout: &Layout) -> Result<Vec<PathBuf>, StorageError> {
+    let mut files = Vec::new();
+    let root = data_path.join(layout.root());
+    if root.exists() {
+        collect_files(data_path, &root, &mut files)?;
+    }
+    files.retain(|path| path.strip_prefix(data_path).is_ok_and(|relative| {
+        layout.matches(relative) || layout.date_of(relative).is_some()
+    }));
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(data_path: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), StorageError> {
+    for entry in fs::read_dir(dir).map_err(|e| StorageError::io(dir, e))? {
+        let path = entry.map_err(|e| StorageError::io(dir, e))?.path();
+        // Projects, the database and the hidden files are never day files
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        if name.starts_with('.') || (dir == data_path && (name == PROYECTS_PATH || name == DB_PATH)) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(data_path, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Files that keep several days have a "# YYYY-MM-DD" line before each day
+const SECTION_PREFIX: &str = "# ";
+const SECTION_DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub fn read_grouped_day_logs(file: &Path) -> Result<BTreeMap<NaiveDate, DayLog>, StorageError> {
+    let mut days = BTreeMap::new();
+    if !file.exists() {
+        return Ok(days);
+    }
+    let content = fs::read_to_string(file).map_err(|e| StorageError::io(file, e))?;
+    let malformed = |line: usize, content: &str, reason: String| StorageError::MalformedLine {
+        path: file.to_path_buf(),
+        error: ParseError { line, content: content.to_string(), reason },
+    };
+
+    // Each day with the number of its header line, to report lines of the whole file
+    let mut sections: Vec<(NaiveDate, usize, Vec<&str>)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if let Some(header) = line.strip_prefix(SECTION_PREFIX) {
+            let date = NaiveDate::parse_from_str(header.trim(), SECTION_DATE_FORMAT)
+                .map_err(|_| malformed(index + 1, line, "not a \"# YYYY-MM-DD\" day header".to_string()))?;
+            sections.push((date, index + 1, Vec::new()));
+        } else if let Some((_, _, lines)) = sections.last_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(malformed(index + 1, line, "entry before the first day header".to_string()));
+        }
+    }
+
+    let last = sections.len();
+    for (index, (date, header_line, lines)) in sections.into_iter().enumerate() {
+        // Only the last day of the file keeps the trailing '\n' that closes an entry
+        let mut section = lines.join("\n");
+        if index + 1 == last && content.ends_with('\n') {
+            section.push('\n');
+        }
+        let day = day_log::parse_day_log(&section)
+            .map_err(|error| malformed(header_line + error.line, &error.content, error.reason))?;
+        days.insert(date, day);
+    }
+    Ok(days)
+}
+
+pub fn write_grouped_day_logs(file: &Path, days: &BTreeMap<NaiveDate, DayLog>) -> Result<(), StorageError> {
+    let mut content = String::new();
+    for (date, day) in days.iter().filter(|(_, day)| !day.entries.is_empty()) {
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!("{}{}\n{}", SECTION_PREFIX, date.format(SECTION_DATE_FORMAT), day));
+    }
+    create_parent_folder(file)?;
+    fs::write(file, content).map_err(|e| StorageError::io(file, e))
+}
+
+#[derive(Debug, Default)]
+pub struct NameMigrationReport {
+    pub rewritten_files: usize,
+    pub migrated_entries: usize,
+    // Lines that were left in the old format, with the reason
+    pub unmapped: Vec<(PathBuf, String, String)>,
+}
+
+// Rewrites the old "project_task" lines of every day file with quoted names.
+// The old form replaced spaces with '-', so the names are looked up in the projects
+// folder and only lines with exactly one possible project and task are changed
+pub fn migrate_legacy_names(data_path: &Path) -> Result<NameMigrationReport> {
+    let mut known: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for project in get_projects(data_path)? {
+        for task in get_tasks_from_project(data_path, &project)? {
+            known.entry(day_log::legacy_name(&project, &task))
+                .or_default()
+                .push((project.clone(), task));
+        }
+    }
+
+    let storage = TextStorage::open(data_path)?;
+    let mut report = NameMigrationReport::default();
+    let mut rewritten_files = std::collections::BTreeSet::new();
+    for date in storage.days()? {
+        let day_file = storage.day_file(date);
+        let mut day = match storage.read_day(date) {
+            Ok(day) => day,
+            Err(e) => {
+                report.unmapped.push((day_file.clone(), String::new(), format!("{:#}", e)));
+                continue;
+            }
+        };
+
+        let mut changed = false;
+        for entry in day.entries.iter_mut().filter(|e| e.legacy) {
+            let name = format!("{}_{}", entry.project, entry.task);
+            let candidates = known.get(&name).cloned().unwrap_or_default();
+            let mapped = match candidates.as_slice() {
+                [(project, task)] => Some((project.clone(), task.clone())),
+                // Without '-' and with a single '_' there was nothing to lose
+                [] if !name.contains('-') && name.matches('_').count() == 1 => {
+                    Some((entry.project.clone(), entry.task.clone()))
+                },
+                [] => {
+                    report.unmapped.push((day_file.clone(), entry.to_string(), "unknown project or task".to_string()));
+                    None
+                },
+                _ => {
+                    report.unmapped.push((day_file.clone(), entry.to_string(), format!("{} possible project/task pairs", candidates.len())));
+                    None
+                },
+            };
+            if let Some((project, task)) = mapped {
+                entry.project = project;
+                entry.task = task;
+                entry.legacy = false;
+                report.migrated_entries += 1;
+                changed = true;
+            }
+        }
+
+        // Files are rewritten where they are, even if they are in the wrong week
+        if changed {
+            if storage.layout.groups_days() {
+                storage.write_day(date, &day)?;
+            } else {
+                write_day_log(&day_file, &day)?;
             }
+            rewritten_files.insert(day_file);
         }
-        Err(e) => panic!("Ocurrió un error inesperado: {}", e),
     }
+    report.rewritten_files = rewritten_files.len();
+    Ok(report)
 }
 
-pub fn start_timer_on_task(data_path: &PathBuf, project_name: &str, task_name: &str) -> Result<()> {
-    match OpenOptions::new()
-        .write(true)
-        .append(true)
-        .create(true)
-        .open(get_todays_filename(data_path)) {
-            Ok(mut file) => {
-                if let Err(e) = write!(file, "{} {}_{} (",
-                    Local::now().format("%H:%M"),
-                    project_name.replace(" ", "-").replace(".txt", ""),
-                    task_name.replace(" ", "-")
-                ) {
-                    eprintln!("Error writing to file: {}", e);
-                }
-                Ok(())
+#[derive(Debug, Default)]
+pub struct WeekMigrationReport {
+    // Day files outside the folder of their ISO week, with where they belong
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    // Files that could not be moved because the right one already exists
+    pub conflicts: Vec<(PathBuf, PathBuf)>,
+}
+
+// Moves every day file to the folder of its ISO week-year. With `dry_run` nothing
+// is touched and the report only lists what would be moved
+pub fn migrate_weeks(data_path: &Path, dry_run: bool) -> Result<WeekMigrationReport> {
+    let layout = Layout::load(data_path)?;
+    let mut report = WeekMigrationReport::default();
+    // Files with several days can't be in the wrong place
+    if layout.groups_days() {
+        return Ok(report);
+    }
+    for day_file in get_day_files(data_path, &layout)? {
+        let Some(date) = day_file.strip_prefix(data_path).ok().and_then(|relative| layout.date_of(relative)) else {
+            continue;
+        };
+        let target = layout.path(data_path, date);
+        if day_file == target {
+            continue;
+        }
+        if target.exists() {
+            report.conflicts.push((day_file, target));
+            continue;
+        }
+        if !dry_run {
+            if let Some(folder_path) = target.parent() {
+                fs::create_dir_all(folder_path)
+                    .with_context(|| format!("Could not create folder: {}", folder_path.display()))?;
             }
-            Err(e) => {
-                eprintln!("Error writing to file: {}", e);
-                return Err(anyhow::anyhow!("Error writing to file"));
+            fs::rename(&day_file, &target)
+                .with_context(|| format!("Could not move {} to {}", day_file.display(), target.display()))?;
+            // The old week folder is removed once it is empty
+            if let Some(folder_path) = day_file.parent() {
+                let _ = fs::remove_dir(folder_path);
+            }
+        }
+        report.moves.push((day_file, target));
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Default)]
+pub struct ReorganizeReport {
+    pub days: usize,
+    pub files_before: usize,
+    pub files_after: usize,
+}
+
+// Folder where reorganize prepares the new files and keeps the old ones until it is done
+pub const REORGANIZE_PATH: &str = ".reorganize";
+
+// Moves every day into the files of `layout` and makes it the layout of the data folder.
+// The new files are written and checked apart first, then swapped with the old ones;
+// if anything fails on the way the old files are put back
+pub fn reorganize(data_path: &Path, layout: &Layout) -> Result<ReorganizeReport> {
+    let staging = data_path.join(REORGANIZE_PATH);
+    if staging.exists() {
+        bail!("A previous reorganize was interrupted, its files are in {}", staging.display());
+    }
+
+    let old = TextStorage::open(data_path)?;
+    let old_files = get_day_files(data_path, &old.layout)?;
+    let mut days = BTreeMap::new();
+    for date in old.days()? {
+        days.insert(date, old.read_day(date)?);
+    }
+
+    let staged = TextStorage::with_layout(&staging.join("new"), layout.clone());
+    for (date, day) in &days {
+        staged.write_day(*date, day)?;
+    }
+    for (date, day) in &days {
+        if staged.read_day(*date)?.to_string() != day.to_string() {
+            bail!("The day {} changed when written with the new layout", date);
+        }
+    }
+    let new_files = get_day_files(&staged.data_path, layout)?;
+
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let result = (|| -> Result<()> {
+        for file in &old_files {
+            let relative = file.strip_prefix(data_path)?;
+            move_file(file, &staging.join("old").join(relative), &mut moved)?;
+        }
+        for file in &new_files {
+            let relative = file.strip_prefix(&staged.data_path)?;
+            let target = data_path.join(relative);
+            if target.exists() {
+                bail!("{} is in the way of the new layout", target.display());
+            }
+            move_file(file, &target, &mut moved)?;
+        }
+        layout.save(data_path)
+    })();
+    if let Err(e) = result {
+        for (from, to) in moved.iter().rev() {
+            let _ = fs::rename(to, from);
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e.context("The data folder was left as it was"));
+    }
+
+    for file in &old_files {
+        remove_empty_folders(data_path, file);
+    }
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .with_context(|| format!("Could not remove {}", staging.display()))?;
+    }
+    Ok(ReorganizeReport { days: days.len(), files_before: old_files.len(), files_after: new_files.len() })
+}
+
+fn move_file(from: &Path, to: &Path, moved: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
+    if let Some(folder_path) = to.parent() {
+        fs::create_dir_all(folder_path)
+            .with_context(|| format!("Could not create folder: {}", folder_path.display()))?;
+    }
+    fs::rename(from, to)
+        .with_context(|| format!("Could not move {} to {}", from.display(), to.display()))?;
+    moved.push((from.to_path_buf(), to.to_path_buf()));
+    Ok(())
+}
+
+// Removes the folders of a moved file that were left empty, up to the data folder
+fn remove_empty_folders(data_path: &Path, file: &Path) {
+    let mut folder = file.parent();
+    while let Some(path) = folder.filter(|path| *path != data_path && path.starts_with(data_path)) {
+        if fs::remove_dir(path).is_err() {
+            break;
+        }
+        folder = path.parent();
+    }
+}
+
+// Storage backed by the projects folder and the day files of `data_path`
+pub struct TextStorage {
+    data_path: PathBuf,
+    layout: Layout,
+    clock: Rc<dyn Clock>,
+}
+
+impl TextStorage {
+    // Uses the layout written in the data folder
+    pub fn open(data_path: &Path) -> Result<TextStorage> {
+        Ok(TextStorage::with_layout(data_path, Layout::load(data_path)?))
+    }
+
+    pub fn with_layout(data_path: &Path, layout: Layout) -> TextStorage {
+        TextStorage { data_path: data_path.to_path_buf(), layout, clock: Rc::new(SystemClock) }
+    }
+
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> TextStorage {
+        self.clock = clock;
+        self
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    // The file the day is read from
+    fn day_file(&self, date: NaiveDate) -> PathBuf {
+        if self.layout.groups_days() {
+            self.layout.path(&self.data_path, date)
+        } else {
+            find_day_file(&self.data_path, &self.layout, date)
+        }
+    }
+}
+
+impl Storage for TextStorage {
+    fn init(&self) -> Result<()> {
+        init(&self.data_path, &self.layout, self.clock.today())?;
+        Ok(())
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    // Sorted like the other backends, read_dir gives them in no particular order
+    fn get_projects(&self) -> Result<Vec<String>> {
+        let mut projects = get_projects(&self.data_path)?;
+        projects.sort();
+        Ok(projects)
+    }
+
+    fn get_tasks(&self, project: &str) -> Result<Vec<String>> {
+        Ok(get_tasks_from_project(&self.data_path, project)?)
+    }
+
+    fn create_project(&self, project: &str) -> Result<()> {
+        create_project(&self.data_path, project)?;
+        Ok(())
+    }
+
+    fn create_task(&self, project: &str, task: &str) -> Result<()> {
+        Ok(create_task(&self.data_path, project, task)?)
+    }
+
+    fn read_day(&self, date: NaiveDate) -> Result<DayLog> {
+        if self.layout.groups_days() {
+            let mut days = read_grouped_day_logs(&self.day_file(date))?;
+            return Ok(days.remove(&date).unwrap_or_default());
+        }
+        Ok(read_day_log(&self.day_file(date))?)
+    }
+
+    // A misfiled copy is replaced by the file in the right folder
+    fn write_day(&self, date: NaiveDate, day: &DayLog) -> Result<()> {
+        let day_file = self.layout.path(&self.data_path, date);
+        if self.layout.groups_days() {
+            let mut days = read_grouped_day_logs(&day_file)?;
+            days.insert(date, day.clone());
+            return Ok(write_grouped_day_logs(&day_file, &days)?);
+        }
+        write_day_log(&day_file, day)?;
+        let misfiled = get_misfiled_day_filename(&self.data_path, date);
+        if self.layout.is_default() && misfiled != day_file && misfiled.exists() {
+            fs::remove_file(&misfiled)
+                .with_context(|| format!("Could not remove misfiled day file: {}", misfiled.display()))?;
+        }
+        Ok(())
+    }
+
+    fn days(&self) -> Result<Vec<NaiveDate>> {
+        let mut days = Vec::new();
+        for day_file in get_day_files(&self.data_path, &self.layout)? {
+            if self.layout.groups_days() {
+                days.extend(read_grouped_day_logs(&day_file)?.into_keys());
+            } else if let Some(date) = day_file.strip_prefix(&self.data_path).ok().and_then(|relative| self.layout.date_of(relative)) {
+                days.push(date);
             }
         }
-}
\ No newline at end of file
+        days.sort();
+        days.dedup();
+        Ok(days)
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use crate::data_managing::Storage;
+use crate::data_managing::day_log::{Entry, END_OF_DAY, TIME_FORMAT};
+
+// An entry that was never stopped on a day before today
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dangling {
+    pub date: NaiveDate,
+    pub entry: Entry,
+}
+
+#[derive(Debug, Default)]
+pub struct DoctorReport {
+    pub dangling: Vec<Dangling>,
+    // Days that could not be read, with the reason
+    pub malformed: Vec<(NaiveDate, String)>,
+}
+
+// How a dangling entry gets closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAt {
+    // A time given by the user
+    Time(NaiveTime),
+    // The end of the work schedule, or the start for an entry started after it
+    ScheduleEnd(NaiveTime),
+    // 23:59:59 of its day, continuing at 00:00 when the next day is today
+    Midnight,
+}
+
+// Where a forgotten entry is closed when the user has no schedule yet
+pub const DEFAULT_WORK_END: NaiveTime = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+
+// The entry started yesterday evening that running_entry() still returns is a session
+// that crossed midnight, not a forgotten one
+pub fn check(storage: &dyn Storage, today: NaiveDate) -> Result<DoctorReport> {
+    let mut report = DoctorReport::default();
+    let running = storage.running_entry().ok().flatten().map(|(date, _)| date);
+    for date in storage.days()?.into_iter().filter(|d| *d < today && Some(*d) != running) {
+        match storage.read_day(date) {
+            Ok(day) => {
+                if let Some(entry) = day.open_entry() {
+                    report.dangling.push(Dangling { date, entry: entry.clone() });
+                }
+            },
+            Err(e) => report.malformed.push((date, format!("{:#}", e))),
+        }
+    }
+    Ok(report)
+}
+
+pub fn close_dangling(storage: &dyn Storage, dangling: &Dangling, close_at: CloseAt, today: NaiveDate) -> Result<()> {
+    let mut day = storage.read_day(dangling.date)?;
+    let end = match close_at {
+        CloseAt::Time(end) => end,
+        // Nothing after the work day is counted for an entry started in the evening
+        CloseAt::ScheduleEnd(end) => end.max(dangling.entry.start),
+        CloseAt::Midnight => END_OF_DAY,
+    };
+    day.check_end(end)?;
+    if let Some(open) = day.open_entry_mut() {
+        open.close(end, "");
+    }
+    storage.write_day(dangling.date, &day)?;
+
+    // The part after midnight goes to today, running if today is still empty
+    // or closed when the first entry of today starts
+    if close_at == CloseAt::Midnight && dangling.date + TimeDelta::days(1) == today {
+        let mut today_log = storage.read_day(today)?;
+        let mut continuation = Entry::new(NaiveTime::MIN, &dangling.entry.project, &dangling.entry.task);
+        if let Some(first) = today_log.entries.first() {
+            continuation.close(first.start, "");
+        }
+        today_log.entries.insert(0, continuation);
+        storage.write_day(today, &today_log)?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Dangling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} / {} started at {}", self.date, self.entry.project, self.entry.task, self.entry.start.format(TIME_FORMAT))
+    }
+}
@@ -0,0 +1,259 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+// Points of a matched character and bonuses for where it matched, in the spirit of fzf
+const SCORE_MATCH: i64 = 16;
+const GAP_START: i64 = -3;
+const GAP_EXTENSION: i64 = -1;
+// After a space, '-', '_', '/'... or at the start of the candidate
+const BONUS_BOUNDARY: i64 = 8;
+// "fixBug" or "v2"
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+// The first character of the query counts its bonus twice
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+// Typing "proj" is closer to "project-alpha" than to "big-projection"
+const BONUS_PREFIX: i64 = 8;
+const BONUS_CASE: i64 = 1;
+
+// A candidate that contains the query as a subsequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    // Position in the candidates given to top_k
+    pub index: usize,
+    pub score: i64,
+    // Char indexes of the candidate that matched the query, to highlight them
+    pub positions: Vec<usize>,
+}
+
+// The query prepared once and compared against every candidate
+pub struct Matcher {
+    query: Vec<char>,
+    folded: Vec<char>,
+}
+
+impl Matcher {
+    pub fn new(query: &str) -> Matcher {
+        // A typed accent can come as its own mark, "diseño" is compared as "diseno" anyway
+        let query: Vec<char> = query.trim().chars().filter(|c| !is_combining_mark(*c)).collect();
+        let folded = query.iter().map(|c| fold(*c)).collect();
+        Matcher { query, folded }
+    }
+
+    // None when the candidate doesn't contain every character of the query in order.
+    // An empty query matches everything with score 0
+    pub fn score(&self, candidate: &str) -> Option<(i64, Vec<usize>)> {
+        let mut positions = Vec::with_capacity(self.query.len());
+        let score = self.score_into(candidate, &mut positions)?;
+        Some((score, positions))
+    }
+
+    fn score_into(&self, candidate: &str, positions: &mut Vec<usize>) -> Option<i64> {
+        positions.clear();
+        if self.query.is_empty() {
+            return Some(0);
+        }
+        // Cheap rejection before anything is allocated, most candidates end here
+        let mut pending = self.folded.iter().peekable();
+        for c in candidate.chars() {
+            if pending.peek().is_some_and(|q| **q == fold(c)) {
+                pending.next();
+            }
+        }
+        if pending.peek().is_some() {
+            return None;
+        }
+
+        let chars: Vec<char> = candidate.chars().collect();
+        let end = self.forward_end(&chars)?;
+        let start = self.backward_start(&chars, end)?;
+        // Inside the shortest window the query is matched again from the left
+        let mut qi = 0;
+        for (pos, c) in chars.iter().enumerate().take(end + 1).skip(start) {
+            if qi < self.folded.len() && fold(*c) == self.folded[qi] {
+                positions.push(pos);
+                qi += 1;
+            }
+        }
+        let score = self.score_positions(&chars, positions);
+        // "pq3" on "Planificación Q3", the window can miss the initials of the words
+        if let Some(initials) = self.initials(&chars) {
+            let initials_score = self.score_positions(&chars, &initials);
+            if initials_score > score {
+                *positions = initials;
+                return Some(initials_score);
+            }
+        }
+        Some(score)
+    }
+
+    // Every character of the query either follows the one before it in the same word
+    // or starts a later word, like "plq3" on "Planificación Q3"
+    fn initials(&self, chars: &[char]) -> Option<Vec<usize>> {
+        let mut positions: Vec<usize> = Vec::with_capacity(self.folded.len());
+        for q in &self.folded {
+            let next = match positions.last() {
+                Some(&last) if chars.get(last + 1).is_some_and(|c| fold(*c) == *q) => last + 1,
+                last => {
+                    let from = last.map_or(0, |last| last + 1);
+                    (from..chars.len()).find(|&pos| fold(chars[pos]) == *q && bonus_at(chars, pos) > 0)?
+                },
+            };
+            positions.push(next);
+        }
+        Some(positions)
+    }
+
+    // Last character of the first greedy match
+    fn forward_end(&self, chars: &[char]) -> Option<usize> {
+        let mut qi = 0;
+        for (pos, c) in chars.iter().enumerate() {
+            if fold(*c) == self.folded[qi] {
+                qi += 1;
+                if qi == self.folded.len() {
+                    return Some(pos);
+                }
+            }
+        }
+        None
+    }
+
+    // Going back from `end`, where the tightest match starts
+    fn backward_start(&self, chars: &[char], end: usize) -> Option<usize> {
+        let mut qi = self.folded.len();
+        for pos in (0..=end).rev() {
+            if fold(chars[pos]) == self.folded[qi - 1] {
+                qi -= 1;
+                if qi == 0 {
+                    return Some(pos);
+                }
+            }
+        }
+        None
+    }
+
+    fn score_positions(&self, chars: &[char], positions: &[usize]) -> i64 {
+        let mut score = 0;
+        let mut previous: Option<usize> = None;
+        let mut chunk_bonus = 0;
+        for (qi, &pos) in positions.iter().enumerate() {
+            let bonus = bonus_at(chars, pos);
+            score += SCORE_MATCH;
+            match previous {
+                // A run keeps the bonus of where it started
+                Some(prev) if pos == prev + 1 => {
+                    chunk_bonus = chunk_bonus.max(bonus).max(BONUS_CONSECUTIVE);
+                    score += chunk_bonus;
+                },
+                Some(prev) => {
+                    score += GAP_START + GAP_EXTENSION * (pos - prev - 2) as i64;
+                    chunk_bonus = bonus;
+                    score += bonus;
+                },
+                None => {
+                    chunk_bonus = bonus;
+                    score += bonus * BONUS_FIRST_CHAR_MULTIPLIER;
+                    if pos == 0 {
+                        score += BONUS_PREFIX;
+                    }
+                },
+            }
+            if chars[pos] == self.query[qi] {
+                score += BONUS_CASE;
+            }
+            previous = Some(pos);
+        }
+        score
+    }
+}
+
+// The best `k` candidates, best first. Ties go to the shorter candidate and then
+// to the one given first, so an empty query keeps the original order
+pub fn top_k(query: &str, candidates: &[String], k: usize) -> Vec<Match> {
+    top_k_boosted(query, candidates, &[], k)
+}
+
+// Like top_k with `boosts[i]` added to the score of `candidates[i]`, like how much
+// each one is used. Missing boosts count as 0. With an empty query only the boosts
+// order the candidates, the length is not looked at
+pub fn top_k_boosted(query: &str, candidates: &[String], boosts: &[i64], k: usize) -> Vec<Match> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let matcher = Matcher::new(query);
+    // Min-heap on the ranking, the worst kept match is on top and is the one replaced
+    let mut heap: BinaryHeap<Reverse<Ranked>> = BinaryHeap::with_capacity(k + 1);
+    let mut positions = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let Some(score) = matcher.score_into(candidate, &mut positions) else {
+            continue;
+        };
+        let score = score + boosts.get(index).copied().unwrap_or(0);
+        let length = if matcher.query.is_empty() { 0 } else { candidate.chars().count() };
+        let ranked = Ranked { score, length, index, positions: Vec::new() };
+        if heap.len() == k && heap.peek().is_some_and(|Reverse(worst)| ranked.cmp(worst) != Ordering::Greater) {
+            continue;
+        }
+        heap.push(Reverse(Ranked { positions: positions.clone(), ..ranked }));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    let mut ranked: Vec<Ranked> = heap.into_iter().map(|Reverse(ranked)| ranked).collect();
+    ranked.sort_by(|a, b| b.cmp(a));
+    ranked.into_iter()
+        .map(|ranked| Match { index: ranked.index, score: ranked.score, positions: ranked.positions })
+        .collect()
+}
+
+// Greater is better
+#[derive(Debug, PartialEq, Eq)]
+struct Ranked {
+    score: i64,
+    length: usize,
+    index: usize,
+    positions: Vec<usize>,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Ranked) -> Ordering {
+        self.score.cmp(&other.score)
+            .then(other.length.cmp(&self.length))
+            .then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Ranked) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Lowercase and without accents, "Ñ" and "ñ" are both "n"
+fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    // The letter comes first once decomposed, the marks after it are dropped
+    let mut base = None;
+    decompose_canonical(c, |d| {
+        base.get_or_insert(d);
+    });
+    let base = base.unwrap_or(c);
+    base.to_lowercase().next().unwrap_or(base)
+}
+
+fn bonus_at(chars: &[char], pos: usize) -> i64 {
+    let Some(&previous) = pos.checked_sub(1).and_then(|p| chars.get(p)) else {
+        return BONUS_BOUNDARY;
+    };
+    let current = chars[pos];
+    if !previous.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (previous.is_lowercase() && current.is_uppercase()) || (!previous.is_numeric() && current.is_numeric()) {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use crate::data_managing::Storage;
+
+// A project and task pair that has been worked on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    pub project: String,
+    pub task: String,
+}
+
+// The last `limit` distinct activities of closed entries, most recent first.
+// Days are read from the newest backwards and the scan stops once enough were found
+pub fn recent_activities(storage: &dyn Storage, limit: usize) -> Result<Vec<Activity>> {
+    let mut activities: Vec<Activity> = Vec::new();
+    for date in storage.days()?.into_iter().rev() {
+        let day = storage.read_day(date)?;
+        for entry in day.entries.iter().rev().filter(|e| !e.is_open()) {
+            if activities.len() == limit {
+                return Ok(activities);
+            }
+            if !activities.iter().any(|a| a.project == entry.project && a.task == entry.task) {
+                activities.push(Activity { project: entry.project.clone(), task: entry.task.clone() });
+            }
+        }
+    }
+    Ok(activities)
+}
+
+// Only the last days count, older ones would not change the order and cost a read each
+const FRECENCY_DAYS: i64 = 90;
+// Points added to the fuzzy score per unit of ln(1 + usage). Every day of the last
+// month is about 30 points, two matched characters
+const FRECENCY_SCALE: f64 = 8.0;
+
+// How often and how recently each project and task was used, as points to add to
+// the fuzzy score of the selector
+#[derive(Debug, Clone, Default)]
+pub struct Frecency {
+    projects: HashMap<String, f64>,
+    tasks: HashMap<(String, String), f64>,
+}
+
+impl Frecency {
+    pub fn project(&self, project: &str) -> i64 {
+        boost(self.projects.get(project))
+    }
+
+    pub fn task(&self, project: &str, task: &str) -> i64 {
+        boost(self.tasks.get(&(project.to_string(), task.to_string())))
+    }
+}
+
+// Every entry of the last FRECENCY_DAYS counts once, more the closer it is to today
+pub fn frecency(storage: &dyn Storage) -> Result<Frecency> {
+    let today = storage.clock().today();
+    let mut frecency = Frecency::default();
+    for date in storage.days()?.into_iter().rev() {
+        let age = (today - date).num_days();
+        if age >= FRECENCY_DAYS {
+            break;
+        }
+        let weight = recency_weight(age);
+        for entry in storage.read_day(date)?.entries {
+            *frecency.projects.entry(entry.project.clone()).or_default() += weight;
+            *frecency.tasks.entry((entry.project, entry.task)).or_default() += weight;
+        }
+    }
+    Ok(frecency)
+}
+
+fn recency_weight(age: i64) -> f64 {
+    match age {
+        ..=1 => 4.0,
+        2..=7 => 2.0,
+        8..=30 => 1.0,
+        _ => 0.5,
+    }
+}
+
+fn boost(usage: Option<&f64>) -> i64 {
+    usage.map_or(0, |usage| (FRECENCY_SCALE * usage.ln_1p()).round() as i64)
+}
@@ -1,5 +1,13 @@
 pub mod utils;
 pub mod data_managing;
+pub mod report;
+pub mod history;
+pub mod doctor;
+pub mod time_spec;
+pub mod config;
+pub mod balance;
+pub mod clock;
+pub mod fuzzy;
 
 //#[cfg(test)]
 //mod tests {
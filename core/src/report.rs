@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeDelta, Weekday};
+use serde::Serialize;
+use crate::data_managing::Storage;
+use crate::data_managing::day_log::Entry;
+
+// Totals are kept in minutes, the smallest unit a day file stores
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskTotal {
+    pub task: String,
+    pub minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProjectTotal {
+    pub project: String,
+    pub minutes: i64,
+    pub tasks: Vec<TaskTotal>,
+}
+
+// Time between the end of an entry and the start of the next one on the same day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Gap {
+    pub date: NaiveDate,
+    pub from: NaiveTime,
+    pub to: NaiveTime,
+    pub minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub minutes: i64,
+    // Biggest project first, same for the tasks inside each project
+    pub projects: Vec<ProjectTotal>,
+    pub gaps: Vec<Gap>,
+    // Entries still running are not counted in the totals
+    pub running: usize,
+}
+
+pub fn day_report(storage: &dyn Storage, date: NaiveDate) -> Result<Report> {
+    range_report(storage, date, date)
+}
+
+// Weeks that don't start on Monday begin on the `week_start` before the ISO Monday
+pub fn week_report(storage: &dyn Storage, year: i32, week: u32, week_start: Weekday) -> Result<Report> {
+    let (from, to) = week_range(year, week, week_start)
+        .ok_or_else(|| anyhow::anyhow!("{}-W{:02} is not a valid ISO week", year, week))?;
+    range_report(storage, from, to)
+}
+
+pub fn week_range(year: i32, week: u32, week_start: Weekday) -> Option<(NaiveDate, NaiveDate)> {
+    let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    let from = monday - TimeDelta::days(days_before_monday(week_start));
+    Some((from, from + TimeDelta::days(6)))
+}
+
+fn days_before_monday(week_start: Weekday) -> i64 {
+    (7 - week_start.num_days_from_monday() as i64) % 7
+}
+
+// Both ends included
+pub fn range_report(storage: &dyn Storage, from: NaiveDate, to: NaiveDate) -> Result<Report> {
+    let mut totals: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+    let mut gaps = Vec::new();
+    let mut running = 0;
+
+    // A half closed at 23:59:59 waits for the day after. If its 00:00 half is still
+    // running the whole session counts as running, else both halves are added
+    let mut first_half: Option<(NaiveDate, Entry)> = None;
+    for date in storage.days()?.into_iter().filter(|d| *d >= from && *d <= to) {
+        let day = storage.read_day(date)?;
+        let mut previous_end: Option<NaiveTime> = None;
+        for (index, entry) in day.entries.iter().enumerate() {
+            if let Some(end) = previous_end.filter(|end| *end < entry.start) {
+                gaps.push(Gap { date, from: end, to: entry.start, minutes: (entry.start - end).num_minutes() });
+            }
+            if let Some((half_date, half)) = first_half.take()
+                && !(half_date + TimeDelta::days(1) == date && entry.is_open() && continues(&half, entry)) {
+                add_entry(&mut totals, &half);
+            }
+            if index + 1 == day.entries.len() && entry.continues_next_day() {
+                first_half = Some((date, entry.clone()));
+            } else if entry.is_open() {
+                running += 1;
+            } else {
+                add_entry(&mut totals, entry);
+            }
+            previous_end = entry.end;
+        }
+    }
+    if let Some((_, half)) = first_half {
+        add_entry(&mut totals, &half);
+    }
+
+    let mut projects: Vec<ProjectTotal> = totals.into_iter()
+        .map(|(project, tasks)| {
+            let mut tasks: Vec<TaskTotal> = tasks.into_iter()
+                .map(|(task, minutes)| TaskTotal { task, minutes })
+                .collect();
+            tasks.sort_by_key(|t| std::cmp::Reverse(t.minutes));
+            ProjectTotal { project, minutes: tasks.iter().map(|t| t.minutes).sum(), tasks }
+        })
+        .collect();
+    projects.sort_by_key(|p| std::cmp::Reverse(p.minutes));
+
+    Ok(Report {
+        from,
+        to,
+        minutes: projects.iter().map(|p| p.minutes).sum(),
+        projects,
+        gaps,
+        running,
+    })
+}
+
+fn add_entry(totals: &mut BTreeMap<String, BTreeMap<String, i64>>, entry: &Entry) {
+    if let Some(duration) = entry.duration() {
+        *totals.entry(entry.project.clone()).or_default()
+            .entry(entry.task.clone()).or_default() += duration.num_minutes();
+    }
+}
+
+// The 00:00 entry that goes on with the session `half` closed at midnight
+fn continues(half: &Entry, entry: &Entry) -> bool {
+    entry.start == NaiveTime::MIN && entry.project == half.project && entry.task == half.task
+}
+
+// "2026-W01", "2026W01" or "2026-W1"
+pub fn parse_iso_week(s: &str) -> Option<(i32, u32)> {
+    let (year, week) = s.split_once('W')?;
+    let year = year.trim_end_matches('-').parse().ok()?;
+    let week = week.parse().ok()?;
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).map(|_| (year, week))
+}
+
+// The week `date` belongs to, named by the ISO week of its Monday
+pub fn current_week(date: NaiveDate, week_start: Weekday) -> (i32, u32) {
+    let start = date - TimeDelta::days(date.weekday().days_since(week_start) as i64);
+    let monday = start + TimeDelta::days(days_before_monday(week_start));
+    (monday.iso_week().year(), monday.iso_week().week())
+}
+
+// "1:05" for 65 minutes
+pub fn format_minutes(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    format!("{}{}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
@@ -0,0 +1,88 @@
+use anyhow::{Result, bail};
+use chrono::{NaiveTime, TimeDelta};
+use crate::data_managing::day_log::TIME_FORMAT;
+
+// When something happened, relative to now or as a time of today
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSpec {
+    Ago(TimeDelta),
+    At(NaiveTime),
+}
+
+impl TimeSpec {
+    // Times are kept inside today and never after `now`
+    pub fn resolve(&self, now: NaiveTime) -> Result<NaiveTime> {
+        let time = match self {
+            TimeSpec::Ago(delta) => {
+                let (time, wrapped) = now.overflowing_sub_signed(*delta);
+                if wrapped != 0 {
+                    bail!("{} minutes ago is before midnight", delta.num_minutes());
+                }
+                time
+            },
+            TimeSpec::At(time) => *time,
+        };
+        if time > now {
+            bail!("{} is in the future", time.format(TIME_FORMAT));
+        }
+        Ok(time)
+    }
+}
+
+// "5m", "1h", "1h30m" or a plain number of minutes
+pub fn parse_ago(s: &str) -> Result<TimeDelta> {
+    let s = s.trim();
+    if let Ok(minutes) = s.parse::<i64>() {
+        return Ok(TimeDelta::minutes(minutes));
+    }
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    for c in s.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' if !number.is_empty() => {
+                let value: i64 = number.parse()?;
+                total += if c == 'h' { TimeDelta::hours(value) } else { TimeDelta::minutes(value) };
+                number.clear();
+            },
+            _ => bail!("'{}' is not a duration like 5m or 1h30m", s),
+        }
+    }
+    if !number.is_empty() || s.is_empty() {
+        bail!("'{}' is not a duration like 5m or 1h30m", s);
+    }
+    Ok(total)
+}
+
+pub fn parse_at(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), TIME_FORMAT)
+        .map_err(|_| anyhow::anyhow!("'{}' is not a HH:MM time", s))
+}
+
+// From the --ago and --at flags, at most one of them is expected
+pub fn from_flags(ago: Option<&str>, at: Option<&str>) -> Result<Option<TimeSpec>> {
+    match (ago, at) {
+        (Some(_), Some(_)) => bail!("--ago and --at can't be used together"),
+        (Some(ago), None) => Ok(Some(TimeSpec::Ago(parse_ago(ago)?))),
+        (None, Some(at)) => Ok(Some(TimeSpec::At(parse_at(at)?))),
+        (None, None) => Ok(None),
+    }
+}
+
+// Prompt input can end with "@-5m" or "@09:40", it is removed from the text.
+// An '@' that isn't followed by a valid time is part of the text, like "user@host:8080"
+pub fn split_inline(input: &str) -> (String, Option<TimeSpec>) {
+    let Some((text, spec)) = input.rsplit_once('@') else {
+        return (input.to_string(), None);
+    };
+    let spec = spec.trim();
+    let parsed = match spec.strip_prefix('-') {
+        Some(ago) => parse_ago(ago).ok().map(TimeSpec::Ago),
+        None => parse_at(spec).ok().map(TimeSpec::At),
+    };
+    match parsed {
+        Some(parsed) => (text.trim_end().to_string(), Some(parsed)),
+        None => (input.to_string(), None),
+    }
+}
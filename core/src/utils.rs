@@ -1,5 +1,6 @@
 // Función para calcular la distancia de Levenshtein entre dos strings.
 //TODO: Posible optimización con distancia levenshtein
+#[allow(clippy::needless_range_loop)]
 pub fn levenshtein_distancia(s1: &str, s2: &str) -> usize {
     let len1 = s1.chars().count();
     let len2 = s2.chars().count();
@@ -24,6 +25,8 @@ pub fn levenshtein_distancia(s1: &str, s2: &str) -> usize {
     matriz[len1][len2]
 }
 
+// Ranking used by the prompt before fuzzy::top_k, kept to compare them in benches/ranking.rs
+#[allow(clippy::bool_comparison, clippy::needless_return)]
 pub fn order_vector(s: &str, v: &Vec<String>) -> Vec<String> {
     let mut result: Vec<String> = Vec::new();
     let mut result_dis: Vec<usize> = Vec::new();
@@ -0,0 +1,38 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use core::balance::balance;
+use core::config::{Schedule, TimeOff};
+use core::data_managing::Storage;
+use core::data_managing::day_log::{DayLog, Entry};
+use core::data_managing::memory_storage::MemoryStorage;
+
+fn worked(storage: &MemoryStorage, date: NaiveDate, hours: u32) {
+    let mut entry = Entry::new(NaiveTime::from_hms_opt(8, 0, 0).unwrap(), "P", "T");
+    entry.close(NaiveTime::from_hms_opt(8 + hours, 0, 0).unwrap(), "");
+    storage.write_day(date, &DayLog { entries: vec![entry] }).unwrap();
+}
+
+#[test]
+fn balance_per_week_and_month_skips_days_off() {
+    let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+    let storage = MemoryStorage::new();
+    // Week 2026-W05 runs from Monday 26-01 to Sunday 01-02
+    worked(&storage, date(1, 26), 9);
+    worked(&storage, date(1, 27), 8);
+    worked(&storage, date(1, 28), 8);
+    worked(&storage, date(1, 31), 2);
+    worked(&storage, date(2, 2), 6);
+
+    let mut schedule = Schedule::default();
+    schedule.holidays.push(date(1, 29));
+    schedule.time_off.push(TimeOff { from: date(1, 30), to: None });
+
+    let result = balance(&storage, &schedule, Weekday::Mon, date(1, 26), date(2, 2)).unwrap();
+    assert_eq!(result.weeks.len(), 2);
+    assert_eq!((result.weeks[0].period.as_str(), result.weeks[0].expected, result.weeks[0].tracked), ("2026-W05", 3 * 480, 27 * 60));
+    assert_eq!(result.weeks[0].difference, 3 * 60);
+    assert_eq!((result.weeks[1].difference, result.weeks[1].balance), (-2 * 60, 60));
+
+    assert_eq!(result.months.iter().map(|m| m.period.as_str()).collect::<Vec<_>>(), vec!["2026-01", "2026-02"]);
+    assert_eq!(result.months[1].balance, 60);
+    assert_eq!(result.total(), 60);
+}
@@ -0,0 +1,31 @@
+use chrono::{NaiveTime, Weekday};
+use core::config::Config;
+use tempfile::tempdir;
+
+#[test]
+fn missing_keys_take_the_default_values() -> anyhow::Result<()> {
+    let temp_dir = tempdir()?;
+    let config_file = temp_dir.path().join("config.toml");
+    assert_eq!(Config::load(&config_file)?, Config::default());
+
+    std::fs::write(&config_file, "week_start = \"sunday\"\n\n[schedule]\nstart = \"08:30\"\ndays = [\"Mon\", \"Tue\"]\n")?;
+    let config = Config::load(&config_file)?;
+    assert_eq!(config.week_start, Weekday::Sun);
+    assert_eq!(config.schedule.start, NaiveTime::from_hms_opt(8, 30, 0).unwrap());
+    assert_eq!(config.schedule.end, Config::default().schedule.end);
+    assert_eq!(config.schedule.days, vec![Weekday::Mon, Weekday::Tue]);
+    assert_eq!(config.time_format, "%H:%M");
+
+    assert_eq!(config.layout, None);
+
+    std::fs::write(&config_file, "layout = \"{year}-{month}.txt\"\n")?;
+    assert_eq!(Config::load(&config_file)?.layout.as_deref(), Some("{year}-{month}.txt"));
+    std::fs::write(&config_file, "layout = \"{week}.txt\"\n")?;
+    assert!(Config::load(&config_file).is_err());
+
+    std::fs::write(&config_file, "colour = \"red\"\n")?;
+    assert!(Config::load(&config_file).is_err());
+    std::fs::write(&config_file, "[schedule]\nend = \"6pm\"\n")?;
+    assert!(Config::load(&config_file).is_err());
+    Ok(())
+}
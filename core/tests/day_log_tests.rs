@@ -0,0 +1,72 @@
+use chrono::NaiveTime;
+use core::data_managing::day_log::{parse_day_log, Entry};
+
+#[test]
+fn round_trip_closed_and_open_entries() {
+    let content = "09:00 Proyecto-testing_Task-testing (Did (some) stuff) 10:30\n11:00 Otro_Tarea (";
+    let day = parse_day_log(content).unwrap();
+
+    assert_eq!(day.entries.len(), 2);
+    assert_eq!(day.entries[0].project, "Proyecto-testing");
+    assert_eq!(day.entries[0].task, "Task-testing");
+    assert_eq!(day.entries[0].description.as_deref(), Some("Did (some) stuff"));
+    assert_eq!(day.entries[0].end, NaiveTime::from_hms_opt(10, 30, 0));
+    assert_eq!(day.open_entry().map(|e| e.project.as_str()), Some("Otro"));
+    assert_eq!(day.to_string(), content);
+}
+
+#[test]
+fn malformed_lines_report_their_line_number() {
+    let err = parse_day_log("09:00 a_b (x) 10:00\n\n9h a_b (x) 10:00\n").unwrap_err();
+    assert_eq!(err.line, 3);
+
+    // An open entry is only valid at the end of the file
+    let err = parse_day_log("09:00 a_b (\n10:00 a_b (x) 11:00\n").unwrap_err();
+    assert_eq!(err.line, 1);
+}
+
+#[test]
+fn closing_an_entry_serializes_the_end_half() {
+    let mut entry = Entry::new(NaiveTime::from_hms_opt(8, 5, 0).unwrap(), "p", "t");
+    assert_eq!(entry.to_string(), "08:05 \"p\" \"t\" (");
+    entry.close(NaiveTime::from_hms_opt(9, 0, 0).unwrap(), "desc");
+    assert_eq!(entry.to_string(), "08:05 \"p\" \"t\" (desc) 09:00");
+    assert_eq!(entry.duration().map(|d| d.num_minutes()), Some(55));
+}
+
+#[test]
+fn quoted_names_keep_any_character() {
+    let mut entry = Entry::new(NaiveTime::from_hms_opt(9, 0, 0).unwrap(), "data_pipeline", "fix-bug \"now\" (ñ)");
+    entry.close(NaiveTime::from_hms_opt(9, 30, 0).unwrap(), "done");
+    let line = format!("{}\n", entry);
+    assert_eq!(line, "09:00 \"data_pipeline\" \"fix-bug \\\"now\\\" (ñ)\" (done) 09:30\n");
+
+    let day = parse_day_log(&line).unwrap();
+    assert_eq!(day.entries[0], entry);
+    assert!(!day.entries[0].legacy);
+}
+
+#[test]
+fn entries_split_at_midnight_keep_their_seconds() {
+    let content = "23:30 \"P\" \"T\" (Deploy) 23:59:59\n";
+    let day = parse_day_log(content).unwrap();
+    assert!(day.entries[0].continues_next_day());
+    assert_eq!(day.to_string(), content);
+}
+
+#[test]
+fn descriptions_with_line_breaks_stay_on_one_line() {
+    let mut entry = Entry::new(NaiveTime::from_hms_opt(9, 0, 0).unwrap(), "P", "T");
+    for description in ["line one\nline two", "a\r\nb", "C:\\2024", "ends in \\", "\\n is not a break", "\\\nx"] {
+        entry.close(NaiveTime::from_hms_opt(9, 30, 0).unwrap(), description);
+        let line = format!("{}\n", entry);
+        assert_eq!(line.lines().count(), 1, "{:?}", line);
+        assert_eq!(parse_day_log(&line).unwrap().entries[0], entry);
+    }
+    entry.close(NaiveTime::from_hms_opt(9, 30, 0).unwrap(), "line one\nline two");
+    assert_eq!(entry.to_string(), "09:00 \"P\" \"T\" (line one\\nline two) 09:30");
+
+    // Backslashes written before descriptions were escaped read as they were
+    let old = parse_day_log("09:00 \"P\" \"T\" (C:\\2024) 09:30\n").unwrap();
+    assert_eq!(old.entries[0].description.as_deref(), Some("C:\\2024"));
+}
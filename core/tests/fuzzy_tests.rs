@@ -0,0 +1,56 @@
+use core::fuzzy::{top_k, Matcher};
+
+fn names(candidates: &[&str]) -> Vec<String> {
+    candidates.iter().map(|c| c.to_string()).collect()
+}
+
+fn ranked(query: &str, candidates: &[String], k: usize) -> Vec<String> {
+    top_k(query, candidates, k).into_iter().map(|m| candidates[m.index].clone()).collect()
+}
+
+#[test]
+fn prefixes_and_word_boundaries_rank_first() {
+    let candidates = names(&["big-projection", "p-r-o-j", "Proyecto", "project-alpha", "mail"]);
+    assert_eq!(ranked("proj", &candidates, 10), vec!["project-alpha", "big-projection", "p-r-o-j"]);
+
+    let candidates = names(&["lisboa", "logo-bueno", "fixBug"]);
+    assert_eq!(ranked("lb", &candidates, 10), vec!["logo-bueno", "lisboa"]);
+    assert_eq!(ranked("fb", &candidates, 10), vec!["fixBug"]);
+
+    // Same letters, the exact case wins
+    let candidates = names(&["logo", "Logo"]);
+    assert_eq!(ranked("Lo", &candidates, 10), vec!["Logo", "logo"]);
+}
+
+#[test]
+fn positions_and_top_k() {
+    let (_, positions) = Matcher::new("fb").score("fix bug").unwrap();
+    assert_eq!(positions, vec![0, 4]);
+    let (_, positions) = Matcher::new("ño").score("Diseño nuevo").unwrap();
+    assert_eq!(positions, vec![4, 5]);
+    assert!(Matcher::new("xyz").score("fix bug").is_none());
+
+    // An empty query keeps the order, k cuts the list
+    let candidates: Vec<String> = (0..50_000).map(|i| format!("task {}", i)).collect();
+    assert_eq!(ranked("", &candidates, 3), vec!["task 0", "task 1", "task 2"]);
+    assert_eq!(ranked("task 4999", &candidates, 2), vec!["task 4999", "task 49990"]);
+    assert!(top_k("task", &candidates, 0).is_empty());
+}
+
+#[test]
+fn accents_and_case_are_ignored_and_initials_match() {
+    for (query, candidate) in [("diseno", "Diseño"), ("DISEÑO", "diseno"), ("planificacion", "Planificación Q3"), ("e\u{301}xito", "Éxito")] {
+        assert!(Matcher::new(query).score(candidate).is_some(), "{} on {}", query, candidate);
+    }
+    // Typing the accent is a little closer
+    let candidates = names(&["Diseno", "Diseño"]);
+    assert_eq!(ranked("diseño", &candidates, 10), vec!["Diseño", "Diseno"]);
+
+    let candidates = names(&["Presupuesto 2023", "Planificación Q3", "pequeño"]);
+    assert_eq!(ranked("pq3", &candidates, 10), vec!["Planificación Q3"]);
+    let (_, positions) = Matcher::new("pq3").score("Planificación Q3").unwrap();
+    assert_eq!(positions, vec![0, 14, 15]);
+    // Without the tilde the "ñ" is an "n" too, the initials of the words win
+    let (_, positions) = Matcher::new("dn").score("Diseño nuevo").unwrap();
+    assert_eq!(positions, vec![0, 7]);
+}
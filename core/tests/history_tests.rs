@@ -0,0 +1,48 @@
+use std::rc::Rc;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use core::clock::FixedClock;
+use core::data_managing::Storage;
+use core::data_managing::day_log::{DayLog, Entry};
+use core::data_managing::memory_storage::MemoryStorage;
+use core::fuzzy::top_k_boosted;
+use core::history;
+
+// An hour of work on `project` / `task`
+fn entry(project: &str, task: &str) -> Entry {
+    let mut entry = Entry::new(NaiveTime::from_hms_opt(9, 0, 0).unwrap(), project, task);
+    entry.close(NaiveTime::from_hms_opt(10, 0, 0).unwrap(), "");
+    entry
+}
+
+#[test]
+fn frecency_puts_the_daily_projects_and_tasks_first() {
+    let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+    let clock = Rc::new(FixedClock::new(today.and_hms_opt(18, 0, 0).unwrap()));
+    let storage = MemoryStorage::new().with_clock(clock);
+    // Llamadas every day of the last week, Diseño a lot but months ago
+    for days_ago in 0..7 {
+        let day = DayLog { entries: vec![entry("Llamadas", "Clientes")] };
+        storage.write_day(today - TimeDelta::days(days_ago), &day).unwrap();
+    }
+    let old = DayLog { entries: vec![entry("Diseño", "Logo"); 20] };
+    storage.write_day(today - TimeDelta::days(200), &old).unwrap();
+    let recent = DayLog { entries: vec![entry("Admin", "Facturas")] };
+    storage.write_day(today - TimeDelta::days(20), &recent).unwrap();
+
+    let frecency = history::frecency(&storage).unwrap();
+    assert_eq!(frecency.project("Diseño"), 0);
+    assert!(frecency.project("Llamadas") > frecency.project("Admin"));
+    assert!(frecency.task("Admin", "Facturas") > 0);
+    assert_eq!(frecency.task("Llamadas", "Facturas"), 0);
+
+    let projects: Vec<String> = ["Admin", "Diseño", "Llamadas"].iter().map(|p| p.to_string()).collect();
+    let boosts: Vec<i64> = projects.iter().map(|p| frecency.project(p)).collect();
+    let ranked = |query| -> Vec<&str> {
+        top_k_boosted(query, &projects, &boosts, 10).into_iter().map(|m| projects[m.index].as_str()).collect()
+    };
+    // Before typing the most used go first. Typing filters, and a prefix match beats a
+    // little use but one letter is not enough against the project of every day
+    assert_eq!(ranked(""), vec!["Llamadas", "Admin", "Diseño"]);
+    assert_eq!(ranked("d"), vec!["Llamadas", "Diseño", "Admin"]);
+    assert_eq!(ranked("dis"), vec!["Diseño"]);
+}
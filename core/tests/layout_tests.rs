@@ -0,0 +1,31 @@
+use std::path::{Path, PathBuf};
+use chrono::NaiveDate;
+use core::data_managing::layout::Layout;
+
+#[test]
+fn default_layout_uses_iso_week_folders() {
+    let layout = Layout::default();
+    let date = NaiveDate::from_ymd_opt(2025, 12, 30).unwrap();
+    assert_eq!(layout.relative_path(date), PathBuf::from("Weeks/2026 W01/30-12-2025.txt"));
+    assert_eq!(layout.root(), PathBuf::from("Weeks"));
+    assert!(!layout.groups_days());
+    assert_eq!(layout.date_of(Path::new("Weeks/2026 W01/30-12-2025.txt")), Some(date));
+    // A file in the wrong week is still recognised by its name
+    assert_eq!(layout.date_of(Path::new("Weeks/2025 W01/30-12-2025.txt")), Some(date));
+}
+
+#[test]
+fn custom_layouts() {
+    let date = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+    let daily = Layout::parse("{year}/{month}/{date:%Y-%m-%d}.txt").unwrap();
+    assert_eq!(daily.relative_path(date), PathBuf::from("2026/01/2026-01-05.txt"));
+    assert_eq!(daily.date_of(Path::new("2026/01/2026-01-05.txt")), Some(date));
+
+    let monthly = Layout::parse("{year}-{month}.txt").unwrap();
+    assert!(monthly.groups_days());
+    assert!(monthly.matches(Path::new("2026-01.txt")));
+    assert!(!monthly.matches(Path::new("notes.txt")));
+
+    assert!(Layout::parse("{year}/{week}.txt").is_err());
+    assert!(Layout::parse("../{year}.txt").is_err());
+}
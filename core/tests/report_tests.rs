@@ -0,0 +1,125 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use core::data_managing::Storage;
+use core::data_managing::day_log::{DayLog, Entry, END_OF_DAY};
+use core::data_managing::memory_storage::MemoryStorage;
+use core::report::{current_week, day_report, range_report, week_range, week_report, Gap, Report};
+
+fn date(d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+}
+
+fn time(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+}
+
+// A closed entry, or a running one without `end`
+fn entry(start: NaiveTime, end: Option<NaiveTime>, project: &str, task: &str) -> Entry {
+    let mut entry = Entry::new(start, project, task);
+    if let Some(end) = end {
+        entry.close(end, "");
+    }
+    entry
+}
+
+fn write_days(storage: &MemoryStorage, days: Vec<(NaiveDate, Vec<Entry>)>) {
+    for (date, entries) in days {
+        storage.write_day(date, &DayLog { entries }).unwrap();
+    }
+}
+
+// (project, minutes) of each project, biggest first
+fn project_minutes(report: &Report) -> Vec<(&str, i64)> {
+    report.projects.iter().map(|p| (p.project.as_str(), p.minutes)).collect()
+}
+
+#[test]
+fn totals_add_up_tasks_and_projects_biggest_first() {
+    let storage = MemoryStorage::new();
+    write_days(&storage, vec![
+        (date(4), vec![entry(time(9, 0), Some(time(12, 0)), "Fuera", "x")]),
+        (date(5), vec![
+            entry(time(9, 0), Some(time(9, 30)), "A", "uno"),
+            entry(time(9, 30), Some(time(11, 0)), "B", "dos"),
+            entry(time(11, 0), Some(time(11, 45)), "A", "tres"),
+        ]),
+        (date(6), vec![entry(time(9, 0), Some(time(9, 20)), "A", "uno")]),
+    ]);
+
+    let report = range_report(&storage, date(5), date(6)).unwrap();
+    assert_eq!((report.minutes, report.running), (185, 0));
+    assert_eq!(project_minutes(&report), vec![("A", 95), ("B", 90)]);
+    let tasks: Vec<(&str, i64)> = report.projects[0].tasks.iter().map(|t| (t.task.as_str(), t.minutes)).collect();
+    assert_eq!(tasks, vec![("uno", 50), ("tres", 45)]);
+
+    // The week of Monday 05-01-2026 also has the Sunday before it when weeks start on Sunday
+    assert_eq!(week_report(&storage, 2026, 2, Weekday::Mon).unwrap().minutes, 185);
+    assert_eq!(week_report(&storage, 2026, 2, Weekday::Sun).unwrap().minutes, 365);
+}
+
+#[test]
+fn gaps_are_only_between_entries_of_the_same_day() {
+    let storage = MemoryStorage::new();
+    write_days(&storage, vec![
+        (date(5), vec![
+            entry(time(9, 0), Some(time(10, 0)), "A", "uno"),
+            entry(time(10, 0), Some(time(11, 0)), "A", "uno"),
+            entry(time(11, 20), Some(time(12, 0)), "B", "dos"),
+            entry(time(13, 0), Some(time(14, 0)), "B", "dos"),
+        ]),
+        (date(6), vec![entry(time(8, 0), Some(time(9, 0)), "A", "uno")]),
+    ]);
+
+    let report = range_report(&storage, date(5), date(6)).unwrap();
+    assert_eq!(report.gaps, vec![
+        Gap { date: date(5), from: time(11, 0), to: time(11, 20), minutes: 20 },
+        Gap { date: date(5), from: time(12, 0), to: time(13, 0), minutes: 60 },
+    ]);
+    assert!(day_report(&storage, date(6)).unwrap().gaps.is_empty());
+}
+
+#[test]
+fn running_entries_are_counted_apart_from_the_totals() {
+    let storage = MemoryStorage::new();
+    write_days(&storage, vec![
+        (date(5), vec![entry(time(9, 0), Some(time(10, 0)), "A", "uno"), entry(time(10, 30), None, "B", "dos")]),
+    ]);
+
+    let report = day_report(&storage, date(5)).unwrap();
+    assert_eq!((report.minutes, report.running), (60, 1));
+    assert_eq!(project_minutes(&report), vec![("A", 60)]);
+    // The time before the running entry is still a gap
+    assert_eq!(report.gaps, vec![Gap { date: date(5), from: time(10, 0), to: time(10, 30), minutes: 30 }]);
+}
+
+#[test]
+fn weeks_can_start_on_sunday() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    assert_eq!(week_range(2026, 1, Weekday::Mon), Some((date(2025, 12, 29), date(2026, 1, 4))));
+    assert_eq!(week_range(2026, 1, Weekday::Sun), Some((date(2025, 12, 28), date(2026, 1, 3))));
+
+    // Sunday 18-10-2026 opens the week of Monday 19-10-2026
+    assert_eq!(current_week(date(2026, 10, 18), Weekday::Sun), (2026, 43));
+    assert_eq!(current_week(date(2026, 10, 18), Weekday::Mon), (2026, 42));
+}
+
+#[test]
+fn a_session_across_midnight_is_one_entry() {
+    let storage = MemoryStorage::new();
+    write_days(&storage, vec![
+        (date(5), vec![entry(time(9, 0), Some(time(10, 0)), "A", "x"), entry(time(23, 30), Some(END_OF_DAY), "P", "T")]),
+        (date(6), vec![entry(time(0, 0), Some(time(0, 15)), "P", "T")]),
+        (date(7), vec![entry(time(23, 0), Some(END_OF_DAY), "Q", "U")]),
+        (date(8), vec![entry(time(0, 0), None, "Q", "U")]),
+    ]);
+
+    let both = range_report(&storage, date(5), date(6)).unwrap();
+    assert_eq!(project_minutes(&both), vec![("A", 60), ("P", 45)]);
+    assert!(both.gaps.iter().all(|gap| gap.date == date(5)));
+    // A day only counts its own half
+    assert_eq!(project_minutes(&range_report(&storage, date(6), date(6)).unwrap()), vec![("P", 15)]);
+
+    // Still running after midnight, neither half is counted yet
+    let running = range_report(&storage, date(7), date(8)).unwrap();
+    assert_eq!((project_minutes(&running), running.running), (vec![], 1));
+    assert_eq!(project_minutes(&range_report(&storage, date(7), date(7)).unwrap()), vec![("Q", 60)]);
+}
@@ -0,0 +1,249 @@
+use std::fs;
+use std::rc::Rc;
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use core::clock::FixedClock;
+use core::data_managing::Storage;
+use core::data_managing::day_log::{DayLog, Entry, END_OF_DAY};
+use core::data_managing::error::StorageError;
+use core::data_managing::layout::Layout;
+use core::data_managing::lmdb_storage::{self, LmdbStorage, StorageCounts};
+use core::data_managing::memory_storage::MemoryStorage;
+use core::data_managing::text_storage::TextStorage;
+use core::doctor::{self, CloseAt, DEFAULT_WORK_END};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn time(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+}
+
+// A memory storage whose clock stays at `date` `now` until the test moves it
+fn storage_at(date: NaiveDate, now: NaiveTime) -> (MemoryStorage, Rc<FixedClock>) {
+    let clock = Rc::new(FixedClock::new(date.and_time(now)));
+    (MemoryStorage::new().with_clock(clock.clone()), clock)
+}
+
+fn closed(start: NaiveTime, end: NaiveTime, project: &str, task: &str) -> Entry {
+    let mut entry = Entry::new(start, project, task);
+    entry.close(end, "");
+    entry
+}
+
+#[test]
+fn start_and_stop_timer_on_memory_storage() {
+    let today = date(2026, 1, 5);
+    let (storage, clock) = storage_at(today, time(9, 0));
+    storage.create_project("Diseño").unwrap();
+    storage.create_task("Diseño", "Logo nuevo").unwrap();
+    assert!(storage.create_project("Diseño").is_err());
+    assert_eq!(storage.get_tasks("Diseño").unwrap(), vec!["Logo nuevo"]);
+
+    storage.start_timer_on_task("Diseño", "Logo nuevo").unwrap();
+    // Only one entry can run at a time
+    let again = storage.start_timer_on_task("Diseño", "Logo nuevo").unwrap_err();
+    assert!(matches!(again.downcast_ref(), Some(StorageError::AlreadyRunning { start, .. }) if *start == time(9, 0)));
+    let early = storage.stop_timer_at("", time(8, 0)).unwrap_err();
+    assert!(matches!(early.downcast_ref(), Some(StorageError::InvalidTime(_))));
+    assert!(storage.read_day(today).unwrap().open_entry().is_some());
+
+    clock.advance(TimeDelta::minutes(45));
+    assert_eq!(storage.stop_timer("Primer boceto").unwrap().1.start, time(9, 0));
+    let day = storage.read_day(today).unwrap();
+    assert!(day.open_entry().is_none());
+    assert_eq!(day.entries[0].description.as_deref(), Some("Primer boceto"));
+    assert_eq!(day.entries[0].end, Some(time(9, 45)));
+    assert_eq!(storage.days().unwrap(), vec![today]);
+    let stopped = storage.stop_timer("again").unwrap_err();
+    assert!(matches!(stopped.downcast_ref(), Some(StorageError::NothingRunning)));
+    assert!(matches!(storage.cancel_timer().unwrap_err().downcast_ref(), Some(StorageError::NothingRunning)));
+    let overlap = storage.start_timer_at("Diseño", "Logo nuevo", time(9, 30)).unwrap_err();
+    assert!(matches!(overlap.downcast_ref(), Some(StorageError::InvalidTime(_))));
+}
+
+#[test]
+fn dangling_entries_split_at_midnight() {
+    let yesterday = date(2025, 12, 31);
+    let today = date(2026, 1, 1);
+    let (storage, _) = storage_at(today, time(12, 0));
+    storage.write_day(yesterday, &DayLog { entries: vec![Entry::new(time(22, 0), "P", "T")] }).unwrap();
+    storage.write_day(today, &DayLog { entries: vec![closed(time(9, 0), time(10, 0), "Q", "U")] }).unwrap();
+
+    let report = doctor::check(&storage, today).unwrap();
+    assert_eq!(report.dangling.len(), 1);
+    doctor::close_dangling(&storage, &report.dangling[0], CloseAt::Midnight, today).unwrap();
+
+    assert!(storage.read_day(yesterday).unwrap().entries[0].continues_next_day());
+    let today_log = storage.read_day(today).unwrap();
+    assert_eq!(today_log.entries[0].start, time(0, 0));
+    assert_eq!(today_log.entries[0].end, Some(time(9, 0)));
+    assert!(doctor::check(&storage, today).unwrap().dangling.is_empty());
+}
+
+#[test]
+fn closing_at_the_schedule_end_keeps_evening_entries_valid() {
+    let today = date(2026, 1, 8);
+    let (storage, _) = storage_at(today, time(12, 0));
+    for (day, start) in [(date(2026, 1, 5), time(10, 0)), (date(2026, 1, 6), time(20, 30))] {
+        storage.write_day(day, &DayLog { entries: vec![Entry::new(start, "P", "T")] }).unwrap();
+    }
+
+    let report = doctor::check(&storage, today).unwrap();
+    for dangling in &report.dangling {
+        doctor::close_dangling(&storage, dangling, CloseAt::ScheduleEnd(DEFAULT_WORK_END), today).unwrap();
+    }
+    let ends: Vec<_> = report.dangling.iter().map(|d| storage.read_day(d.date).unwrap().entries[0].end).collect();
+    assert_eq!(ends, vec![Some(DEFAULT_WORK_END), Some(time(20, 30))]);
+    assert!(doctor::check(&storage, today).unwrap().dangling.is_empty());
+}
+
+#[test]
+fn a_session_across_midnight_is_not_dangling() {
+    let monday = date(2026, 1, 5);
+    let (storage, clock) = storage_at(monday, time(23, 30));
+    storage.start_timer_on_task("P", "T").unwrap();
+
+    clock.advance(TimeDelta::minutes(75));
+    let today = storage.clock().today();
+    assert_eq!(storage.running_entry().unwrap().map(|(date, _)| date), Some(monday));
+    assert!(doctor::check(&storage, today).unwrap().dangling.is_empty());
+
+    // One left open the day before is still reported
+    let older = monday - TimeDelta::days(1);
+    storage.write_day(older, &DayLog { entries: vec![Entry::new(time(10, 0), "Q", "U")] }).unwrap();
+    let report = doctor::check(&storage, today).unwrap();
+    assert_eq!(report.dangling.iter().map(|d| d.date).collect::<Vec<_>>(), vec![older]);
+}
+
+#[test]
+fn an_entry_forgotten_during_the_day_is_dangling_the_next_morning() {
+    let monday = date(2026, 1, 5);
+    let (storage, clock) = storage_at(monday, time(10, 0));
+    storage.start_timer_on_task("P", "T").unwrap();
+
+    clock.advance(TimeDelta::hours(23));
+    let tuesday = storage.clock().today();
+    assert_eq!(storage.running_entry().unwrap(), None);
+    let report = doctor::check(&storage, tuesday).unwrap();
+    assert_eq!(report.dangling.iter().map(|d| (d.date, d.entry.start)).collect::<Vec<_>>(), vec![(monday, time(10, 0))]);
+
+    doctor::close_dangling(&storage, &report.dangling[0], CloseAt::ScheduleEnd(DEFAULT_WORK_END), tuesday).unwrap();
+    assert_eq!(storage.read_day(monday).unwrap().entries, vec![closed(time(10, 0), DEFAULT_WORK_END, "P", "T")]);
+    assert!(storage.read_day(tuesday).unwrap().entries.is_empty());
+}
+
+#[test]
+fn the_running_session_starts_before_midnight() {
+    let monday = date(2026, 1, 5);
+    let tuesday = date(2026, 1, 6);
+    let (storage, _) = storage_at(tuesday, time(0, 45));
+    storage.write_day(monday, &DayLog { entries: vec![closed(time(23, 30), END_OF_DAY, "P", "T")] }).unwrap();
+    storage.write_day(tuesday, &DayLog { entries: vec![Entry::new(time(0, 0), "P", "T")] }).unwrap();
+
+    assert_eq!(storage.running_entry().unwrap().map(|(date, open)| (date, open.start)), Some((tuesday, time(0, 0))));
+    assert_eq!(storage.running_session().unwrap().map(|(date, open)| (date, open.start)), Some((monday, time(23, 30))));
+}
+
+#[test]
+fn stopping_after_midnight_splits_the_entry() {
+    let yesterday = date(2026, 1, 5);
+    let today = date(2026, 1, 6);
+    let (storage, _) = storage_at(today, time(0, 5));
+    storage.write_day(yesterday, &DayLog { entries: vec![Entry::new(time(23, 30), "P", "T")] }).unwrap();
+    assert_eq!(storage.running_entry().unwrap().map(|(date, _)| date), Some(yesterday));
+
+    storage.stop_timer_at("Deploy", time(0, 0)).unwrap();
+    let first_half = &storage.read_day(yesterday).unwrap().entries[0];
+    assert_eq!(first_half.end, Some(END_OF_DAY));
+    assert_eq!(first_half.duration(), Some(TimeDelta::minutes(30)));
+    let second_half = &storage.read_day(today).unwrap().entries[0];
+    assert_eq!((second_half.start, second_half.end), (time(0, 0), Some(time(0, 0))));
+    assert_eq!(second_half.description.as_deref(), Some("Deploy"));
+    assert!(storage.running_entry().unwrap().is_none());
+}
+
+#[test]
+fn text_storage_returns_typed_errors() {
+    let temp = tempfile::tempdir().unwrap();
+    let storage = TextStorage::open(temp.path()).unwrap();
+    storage.init().unwrap();
+
+    storage.create_project("Diseño").unwrap();
+    let again = storage.create_project("Diseño").unwrap_err();
+    assert!(matches!(again.downcast_ref(), Some(StorageError::AlreadyExists(_))));
+    let missing = storage.get_tasks("Nada").unwrap_err();
+    assert!(matches!(missing.downcast_ref(), Some(StorageError::NotFound(_))));
+
+    let day = date(2026, 1, 5);
+    let path = Layout::default().path(temp.path(), day);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "09:00 \"P\" \"T\" () 10:00\nbasura\n").unwrap();
+    match storage.read_day(day).unwrap_err().downcast_ref() {
+        Some(StorageError::MalformedLine { error, .. }) => assert_eq!(error.line, 2),
+        other => panic!("expected a malformed line, got {:?}", other),
+    }
+}
+
+#[test]
+fn text_storage_keeps_any_project_and_task_name() {
+    let temp = tempfile::tempdir().unwrap();
+    let storage = TextStorage::open(temp.path()).unwrap();
+    storage.init().unwrap();
+
+    for project in ["CI/CD", "50% off", "C:\\dev", "notas.txt", "dos\nlíneas"] {
+        storage.create_project(project).unwrap();
+        storage.create_task(project, "a\nb").unwrap();
+        storage.create_task(project, "C:\\2024").unwrap();
+        assert_eq!(storage.get_tasks(project).unwrap(), vec!["a\nb", "C:\\2024"], "{}", project);
+    }
+    assert_eq!(storage.get_projects().unwrap(), vec!["50% off", "C:\\dev", "CI/CD", "dos\nlíneas", "notas.txt"]);
+    assert!(temp.path().join("Projects").join("CI%2FCD.txt").exists());
+    assert_eq!(fs::read_to_string(temp.path().join("Projects").join("CI%2FCD.txt")).unwrap(), "a\\nb\nC:\\2024\n");
+
+    // Files written by hand keep a '%' that is not an escape
+    fs::write(temp.path().join("Projects").join("100%.txt"), "").unwrap();
+    assert!(storage.get_projects().unwrap().contains(&"100%".to_string()));
+}
+
+#[test]
+fn importing_the_text_storage_replaces_the_database_at_once() {
+    let temp = tempfile::tempdir().unwrap();
+    let text = TextStorage::open(temp.path()).unwrap();
+    text.init().unwrap();
+    text.create_project("Diseño").unwrap();
+    text.create_task("Diseño", "Logo").unwrap();
+    text.write_day(date(2026, 1, 5), &DayLog {
+        entries: vec![closed(time(9, 0), time(10, 0), "Diseño", "Logo"), closed(time(10, 0), time(11, 0), "Otro", "Llamada")],
+    }).unwrap();
+
+    let storage = LmdbStorage::open(temp.path()).unwrap();
+    let report = lmdb_storage::import_text_storage(temp.path(), &storage).unwrap();
+    assert_eq!(report.expected, StorageCounts { projects: 2, tasks: 2, entries: 2 });
+    assert!(report.is_complete());
+
+    // A day file that can't be read stops the import before the database changes
+    let path = Layout::default().path(temp.path(), date(2026, 1, 6));
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "basura\n").unwrap();
+    assert!(lmdb_storage::import_text_storage(temp.path(), &storage).is_err());
+    assert_eq!(storage.counts().unwrap(), report.stored);
+}
+
+#[test]
+fn fixed_clock_drives_the_timer() {
+    let sunday = date(2026, 1, 4);
+    let (storage, clock) = storage_at(sunday, time(23, 40));
+    clock.advance(TimeDelta::seconds(25));
+    assert_eq!(storage.clock().current_minute(), time(23, 40));
+
+    storage.start_timer_on_task("P", "T").unwrap();
+    clock.advance(TimeDelta::minutes(30));
+    assert_eq!(storage.clock().today(), sunday.succ_opt().unwrap());
+    assert!(storage.running_entry().unwrap().is_some());
+
+    storage.stop_timer("late").unwrap();
+    assert_eq!(storage.read_day(sunday).unwrap().entries[0].start, time(23, 40));
+    assert_eq!(storage.read_day(storage.clock().today()).unwrap().entries[0].end, Some(time(0, 10)));
+    assert_eq!(storage.days().unwrap(), vec![sunday, sunday.succ_opt().unwrap()]);
+}
@@ -0,0 +1,30 @@
+use chrono::{NaiveTime, TimeDelta};
+use core::time_spec::{parse_ago, split_inline, TimeSpec};
+
+fn time(h: u32, m: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(h, m, 0).unwrap()
+}
+
+#[test]
+fn durations_and_inline_specs() {
+    assert_eq!(parse_ago("5m").unwrap(), TimeDelta::minutes(5));
+    assert_eq!(parse_ago("1h30m").unwrap(), TimeDelta::minutes(90));
+    assert_eq!(parse_ago("15").unwrap(), TimeDelta::minutes(15));
+    assert!(parse_ago("5 mins").is_err());
+
+    assert_eq!(split_inline("Revisar PR @-5m"),
+        ("Revisar PR".to_string(), Some(TimeSpec::Ago(TimeDelta::minutes(5)))));
+    assert_eq!(split_inline("Llamada @09:40"),
+        ("Llamada".to_string(), Some(TimeSpec::At(time(9, 40)))));
+    // An '@' without a valid time stays in the text
+    for text in ["mail to a@b", "deploy user@host:8080", "rename @-word", "ratio @a:b", "@-", "late @25:00"] {
+        assert_eq!(split_inline(text), (text.to_string(), None));
+    }
+}
+
+#[test]
+fn resolved_times_stay_in_the_past_of_today() {
+    assert_eq!(TimeSpec::Ago(TimeDelta::minutes(5)).resolve(time(10, 0)).unwrap(), time(9, 55));
+    assert!(TimeSpec::Ago(TimeDelta::minutes(5)).resolve(time(0, 2)).is_err());
+    assert!(TimeSpec::At(time(11, 0)).resolve(time(10, 0)).is_err());
+}